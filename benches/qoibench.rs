@@ -0,0 +1,340 @@
+// Rust counterpart of ref_qoi/qoibench.c, benchmarking this crate's encoder & decoder.
+// Run with `cargo bench --bench qoibench -- <iterations> <directory> [options]`
+
+use qoi::{decode, encode, Header, Pixel};
+use std::{
+    collections::HashMap,
+    fs,
+    hint::black_box,
+    path::{Path, PathBuf},
+    process,
+    time::Instant,
+};
+
+#[derive(Debug, Default)]
+struct Options {
+    runs: u32,
+    nowarmup: bool,
+    noverify: bool,
+    noencode: bool,
+    nodecode: bool,
+    norecurse: bool,
+    onlytotals: bool,
+    save: Option<PathBuf>,
+    compare: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct BenchResult {
+    count: u64,
+    px: u64,
+    raw_size: u64,
+    size: u64,
+    encode_time: u64,
+    decode_time: u64,
+}
+
+impl BenchResult {
+    fn add(&mut self, other: &BenchResult) {
+        self.count += other.count;
+        self.px += other.px;
+        self.raw_size += other.raw_size;
+        self.size += other.size;
+        self.encode_time += other.encode_time;
+        self.decode_time += other.decode_time;
+    }
+
+    fn average(&self) -> BenchResult {
+        let count = self.count.max(1);
+        BenchResult {
+            count: 1,
+            px: self.px / count,
+            raw_size: self.raw_size / count,
+            size: self.size / count,
+            encode_time: self.encode_time / count,
+            decode_time: self.decode_time / count,
+        }
+    }
+
+    fn to_line(self, name: &str) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            name, self.count, self.px, self.raw_size, self.size, self.encode_time, self.decode_time
+        )
+    }
+
+    fn from_line(line: &str) -> Option<(String, BenchResult)> {
+        let mut fields = line.split('\t');
+        let name = fields.next()?.to_string();
+        let mut next = || fields.next()?.parse::<u64>().ok();
+        let res = BenchResult {
+            count: next()?,
+            px: next()?,
+            raw_size: next()?,
+            size: next()?,
+            encode_time: next()?,
+            decode_time: next()?,
+        };
+        Some((name, res))
+    }
+}
+
+fn mpps(px: u64, ns: u64) -> f64 {
+    if ns > 0 {
+        px as f64 / (ns as f64 / 1000.0)
+    } else {
+        0.0
+    }
+}
+
+fn change(new: u64, old: u64) -> String {
+    if old == 0 {
+        "     -".to_string()
+    } else {
+        format!("{:+6.1}%", (new as f64 / old as f64 - 1.0) * 100.0)
+    }
+}
+
+fn print_result(res: &BenchResult, baseline: Option<&BenchResult>) {
+    let res = res.average();
+    println!("        decode ms   encode ms   decode mpps   encode mpps   size kb    rate");
+    println!(
+        "qoi:     {:8.1}    {:8.1}      {:8.2}      {:8.2}  {:8}   {:4.1}%",
+        res.decode_time as f64 / 1000000.0,
+        res.encode_time as f64 / 1000000.0,
+        mpps(res.px, res.decode_time),
+        mpps(res.px, res.encode_time),
+        res.size / 1024,
+        res.size as f64 / res.raw_size as f64 * 100.0
+    );
+    if let Some(base) = baseline {
+        let base = base.average();
+        println!(
+            "base:    {:8.1}    {:8.1}      {:8.2}      {:8.2}  {:8}   {:4.1}%",
+            base.decode_time as f64 / 1000000.0,
+            base.encode_time as f64 / 1000000.0,
+            mpps(base.px, base.decode_time),
+            mpps(base.px, base.encode_time),
+            base.size / 1024,
+            base.size as f64 / base.raw_size as f64 * 100.0
+        );
+        println!(
+            "change:  {}     {}                                {}",
+            change(res.decode_time, base.decode_time),
+            change(res.encode_time, base.encode_time),
+            change(res.size, base.size),
+        );
+    }
+    println!();
+}
+
+// Runs `f` a number of times and returns the average time taken in ns.
+// The first run is ignored, unless `nowarmup` is set.
+fn bench_fn<F: FnMut()>(opts: &Options, mut f: F) -> u64 {
+    let mut time = 0;
+    let start = if opts.nowarmup { 1 } else { 0 };
+    for i in start..=opts.runs {
+        let time_start = Instant::now();
+        f();
+        if i > 0 {
+            time += time_start.elapsed().as_nanos() as u64;
+        }
+    }
+    time / opts.runs as u64
+}
+
+fn bench_image(opts: &Options, path: &Path) -> Result<(Header, BenchResult), String> {
+    let encoded = fs::read(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    let (header, pixels) = decode::decode_allocated(&encoded)
+        .map_err(|e| format!("Couldn't decode {}: {:?}", path.display(), e))?;
+
+    if !opts.noverify {
+        let res = encode::encode_allocated(&header, &pixels)
+            .map_err(|e| format!("Couldn't encode {}: {:?}", path.display(), e))?;
+        let (_, roundtrip) = decode::decode_allocated(&res)
+            .map_err(|e| format!("Couldn't decode {}: {:?}", path.display(), e))?;
+        if roundtrip != pixels {
+            return Err(format!(
+                "QOI roundtrip pixel mismatch for {}",
+                path.display()
+            ));
+        }
+    }
+
+    let mut res = BenchResult {
+        count: 1,
+        px: header.pixel_amount() as u64,
+        raw_size: header.pixel_len() as u64,
+        size: encoded.len() as u64,
+        ..Default::default()
+    };
+
+    if !opts.nodecode {
+        let mut out = vec![Pixel::def(); header.pixel_amount()];
+        res.decode_time = bench_fn(opts, || {
+            black_box(decode::decode(black_box(&encoded), &mut out).unwrap());
+        });
+    }
+
+    if !opts.noencode {
+        let mut out = vec![0; header.max_size()];
+        res.encode_time = bench_fn(opts, || {
+            res.size =
+                black_box(encode::encode(&header, black_box(&pixels), &mut out).unwrap()) as u64;
+        });
+    }
+
+    Ok((header, res))
+}
+
+fn bench_directory(
+    opts: &Options,
+    path: &Path,
+    baseline: &HashMap<String, BenchResult>,
+    results: &mut Vec<(String, BenchResult)>,
+    grand_total: &mut BenchResult,
+) -> Result<(), String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("Couldn't open directory {}: {}", path.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    if !opts.norecurse {
+        for dir in entries.iter().filter(|entry| entry.is_dir()) {
+            bench_directory(opts, dir, baseline, results, grand_total)?;
+        }
+    }
+
+    let mut dir_total = BenchResult::default();
+    let files = entries
+        .iter()
+        .filter(|entry| entry.is_file() && entry.extension().is_some_and(|ext| ext == "qoi"));
+    for (i, file) in files.enumerate() {
+        if i == 0 {
+            println!(
+                "## Benchmarking {}/*.qoi -- {} runs\n",
+                path.display(),
+                opts.runs
+            );
+        }
+        let (header, res) = bench_image(opts, file)?;
+        let name = file.display().to_string();
+        if !opts.onlytotals {
+            println!("## {} size: {}x{}", name, header.width, header.height);
+            print_result(&res, baseline.get(&name));
+        }
+        dir_total.add(&res);
+        grand_total.add(&res);
+        results.push((name, res));
+    }
+
+    if dir_total.count > 0 {
+        let name = format!("{}/", path.display());
+        println!("## Total for {}", name);
+        print_result(&dir_total, baseline.get(&name));
+        results.push((name, dir_total));
+    }
+    Ok(())
+}
+
+fn usage() -> ! {
+    println!("Usage: qoibench <iterations> <directory> [options]");
+    println!("Options:");
+    println!("    --nowarmup ....... don't perform a warmup run");
+    println!("    --noverify ....... don't verify qoi roundtrip");
+    println!("    --noencode ....... don't run encoders");
+    println!("    --nodecode ....... don't run decoders");
+    println!("    --norecurse ...... don't descend into directories");
+    println!("    --onlytotals ..... don't print individual image results");
+    println!("    --save <file> .... store the results as a baseline in <file>");
+    println!("    --compare <file> . compare the results against the baseline in <file>");
+    println!("Examples");
+    println!("    cargo bench --bench qoibench -- 10 imgs/");
+    println!("    cargo bench --bench qoibench -- 1 imgs/ --nowarmup --compare base.tsv");
+    process::exit(1);
+}
+
+fn parse_args() -> (Options, PathBuf) {
+    let mut opts = Options {
+        runs: 10,
+        ..Default::default()
+    };
+    let mut positional = Vec::new();
+    // `cargo bench` passes `--bench` to benchmarks without the default harness
+    let mut args = std::env::args().skip(1).filter(|arg| arg != "--bench");
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--nowarmup" => opts.nowarmup = true,
+            "--noverify" => opts.noverify = true,
+            "--noencode" => opts.noencode = true,
+            "--nodecode" => opts.nodecode = true,
+            "--norecurse" => opts.norecurse = true,
+            "--onlytotals" => opts.onlytotals = true,
+            "--save" => opts.save = Some(args.next().unwrap_or_else(|| usage()).into()),
+            "--compare" => opts.compare = Some(args.next().unwrap_or_else(|| usage()).into()),
+            "-h" | "--help" => usage(),
+            _ if arg.starts_with("--") => {
+                println!("Unknown option {}", arg);
+                usage()
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let dir = match positional.as_slice() {
+        [] => PathBuf::from("imgs"),
+        [runs, dir] => {
+            opts.runs = runs.parse().unwrap_or(0);
+            PathBuf::from(dir)
+        }
+        _ => usage(),
+    };
+    if opts.runs == 0 {
+        println!("Invalid number of runs");
+        usage();
+    }
+    (opts, dir)
+}
+
+fn main() {
+    let (opts, dir) = parse_args();
+
+    let baseline: HashMap<String, BenchResult> = match &opts.compare {
+        None => HashMap::new(),
+        Some(path) => match fs::read_to_string(path) {
+            Err(e) => {
+                println!("Couldn't read baseline {}: {}", path.display(), e);
+                process::exit(1);
+            }
+            Ok(content) => content.lines().filter_map(BenchResult::from_line).collect(),
+        },
+    };
+
+    let mut results = Vec::new();
+    let mut grand_total = BenchResult::default();
+    if let Err(e) = bench_directory(&opts, &dir, &baseline, &mut results, &mut grand_total) {
+        println!("{}", e);
+        process::exit(1);
+    }
+
+    if grand_total.count == 0 {
+        println!("No images found in {}", dir.display());
+        return;
+    }
+    let name = format!("# {}", dir.display());
+    println!("# Grand total for {}", dir.display());
+    print_result(&grand_total, baseline.get(&name));
+    results.push((name, grand_total));
+
+    if let Some(path) = &opts.save {
+        let content: String = results
+            .iter()
+            .map(|(name, res)| res.to_line(name) + "\n")
+            .collect();
+        if let Err(e) = fs::write(path, content) {
+            println!("Couldn't write baseline {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
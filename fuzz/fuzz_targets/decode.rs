@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qoi::{decode, Pixel};
+
+// Like ref_qoi/qoifuzz.c, but decoding into a fixed size buffer
+fuzz_target!(|data: &[u8]| {
+    let mut pixels = vec![Pixel::def(); 1 << 16];
+    let _ = decode::decode(data, &mut pixels);
+});
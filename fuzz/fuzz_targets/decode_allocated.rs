@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qoi::decode;
+
+fuzz_target!(|data: &[u8]| {
+    // Valid headers may still request up to `PIXELS_MAX` pixels, which would exceed the fuzzer's memory limit
+    if let Ok(header) = decode::decode_header(data) {
+        if header.pixel_amount() > 1 << 22 {
+            return;
+        }
+    }
+    let _ = decode::decode_allocated(data);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qoi::{decode, HEADER_SIZE, MAGIC};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = decode::decode_header(data) {
+        assert!(data.len() >= HEADER_SIZE);
+        assert!(data.starts_with(&MAGIC));
+        assert!(header.has_valid_dimensions());
+        let _ = (header.pixel_len(), header.max_size());
+    }
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qoi::png;
+
+fuzz_target!(|data: &[u8]| {
+    // Skip huge dimensions in the IHDR chunk, see decode_allocated
+    if let Some(ihdr) = data.get(16..24) {
+        let width = u32::from_be_bytes(ihdr[..4].try_into().unwrap()) as u64;
+        let height = u32::from_be_bytes(ihdr[4..].try_into().unwrap()) as u64;
+        if width * height > 1 << 22 {
+            return;
+        }
+    }
+    if let Ok((header, pixels)) = png::decode(data) {
+        assert_eq!(pixels.len(), header.pixel_amount());
+    }
+});
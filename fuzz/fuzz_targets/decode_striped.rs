@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qoi::{decode, stripe};
+
+fuzz_target!(|data: &[u8]| {
+    // Skip huge dimensions, see decode_allocated
+    if let Ok(header) = decode::decode_header(data) {
+        if header.pixel_amount() > 1 << 22 {
+            return;
+        }
+    }
+    // The stripe count comes from the untrusted stripe table
+    if let Ok((header, pixels)) = stripe::decode_striped(data) {
+        assert_eq!(pixels.len(), header.pixel_amount());
+    }
+});
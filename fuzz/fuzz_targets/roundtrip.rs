@@ -0,0 +1,31 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qoi::{decode, encode, ColorChannel, ColorSpace, Header, Pixel};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&[width, channels], bytes)) = data.split_first_chunk::<2>() else {
+        return;
+    };
+    let channels = if channels & 1 == 0 {
+        ColorChannel::RGB
+    } else {
+        ColorChannel::RGBA
+    };
+    let mut pixels: Vec<Pixel> = bytes.chunks_exact(4).map(|chunk| chunk.into()).collect();
+    if channels == ColorChannel::RGB {
+        pixels.iter_mut().for_each(|px| px.a = 255);
+    }
+    let width = (width as usize).clamp(1, pixels.len().max(1));
+    let height = pixels.len() / width;
+    if height == 0 {
+        return;
+    }
+    pixels.truncate(width * height);
+
+    let header = Header::new(width as u32, height as u32, channels, ColorSpace::SRGB);
+    let encoded = encode::encode_allocated(&header, &pixels).unwrap();
+    let (decoded_header, decoded) = decode::decode_allocated(&encoded).unwrap();
+    assert_eq!(decoded_header, header);
+    assert_eq!(decoded, pixels);
+});
@@ -0,0 +1,28 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use qoi::{decode, scan::ScanIndex};
+
+fuzz_target!(|data: &[u8]| {
+    // [interval] [index size: u16] [serialized index] [encoded image]
+    let Some((&[interval, size_hi, size_lo], rest)) = data.split_first_chunk::<3>() else {
+        return;
+    };
+    let size = u16::from_be_bytes([size_hi, size_lo]) as usize;
+    let (index_bytes, buffer) = rest.split_at(size.min(rest.len()));
+
+    // Scanning only allocates checkpoints, so even huge dimensions are fine
+    let scanned = ScanIndex::scan(buffer, interval as usize);
+
+    // Skip huge dimensions for decoding, see decode_allocated
+    if decode::decode_header(buffer).is_ok_and(|header| header.pixel_amount() > 1 << 22) {
+        return;
+    }
+    if let Ok(index) = scanned {
+        let _ = index.decode(buffer, interval as usize % 8);
+    }
+    // Serialized indices claim arbitrary checkpoints & offsets
+    if let Ok(index) = ScanIndex::from_bytes(index_bytes) {
+        let _ = index.decode(buffer, 4);
+    }
+});
@@ -0,0 +1,200 @@
+use crate::{disasm::Chunk, *};
+use std::fmt;
+
+pub enum AsmError {
+    /// The first instruction isn't `HEADER <width> <height> <channels> <colorspace>`.
+    MissingHeader,
+
+    /// The line starts with an unknown mnemonic.
+    UnknownMnemonic { line: usize, mnemonic: String },
+
+    /// The instruction has the wrong amount of arguments or one isn't an integer.
+    InvalidArguments { line: usize, expected: usize },
+
+    /// An argument doesn't fit into the chunk.
+    ArgumentOutOfRange {
+        line: usize,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+}
+
+impl fmt::Debug for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingHeader => {
+                write!(
+                    f,
+                    "Missing header: The first instruction needs to be HEADER <width> <height> <channels> <colorspace>."
+                )
+            }
+            Self::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "Line {}: Unknown mnemonic {}.", line, mnemonic)
+            }
+            Self::InvalidArguments { line, expected } => {
+                write!(f, "Line {}: Expected {} integer arguments.", line, expected)
+            }
+            Self::ArgumentOutOfRange {
+                line,
+                value,
+                min,
+                max,
+            } => {
+                write!(
+                    f,
+                    "Line {}: Argument {} is out of range, expected {} to {}.",
+                    line, value, min, max
+                )
+            }
+        }
+    }
+}
+
+use AsmError::*;
+
+impl Chunk {
+    // Encodes the chunk, the arguments need to be in the ranges checked by `parse_chunk`
+    pub(crate) fn to_bytes(self) -> Vec<u8> {
+        match self {
+            Chunk::RGB([r, g, b]) => vec![OP_RGB, r, g, b],
+            Chunk::RGBA([r, g, b, a]) => vec![OP_RGBA, r, g, b, a],
+            Chunk::INDEX(index) => vec![OP_INDEX | index],
+            Chunk::DIFF { dr, dg, db } => {
+                let bias = |d: i8| (d + DIFF_BIAS as i8) as u8;
+                vec![OP_DIFF | bias(dr) << 4 | bias(dg) << 2 | bias(db)]
+            }
+            Chunk::LUMA { dg, dr_dg, db_dg } => {
+                let bias = |d: i8| (d + LUMA_BIAS as i8) as u8;
+                vec![
+                    OP_LUMA | (dg + LUMA_GREEN_BIAS as i8) as u8,
+                    bias(dr_dg) << 4 | bias(db_dg),
+                ]
+            }
+            Chunk::RUN(length) => vec![OP_RUN | (length - 1)],
+        }
+    }
+}
+
+// Parses the arguments of line `line`, checking each against its (min, max) range
+fn parse_args<const N: usize>(
+    line: usize,
+    args: &[&str],
+    ranges: [(i64, i64); N],
+) -> Result<[i64; N], AsmError> {
+    if args.len() != N {
+        return Err(InvalidArguments { line, expected: N });
+    }
+    let mut res = [0; N];
+    for ((arg, (min, max)), val) in args.iter().zip(ranges).zip(&mut res) {
+        let value: i64 = match arg.parse() {
+            Ok(value) => value,
+            Err(_) => return Err(InvalidArguments { line, expected: N }),
+        };
+        if !(min..=max).contains(&value) {
+            return Err(ArgumentOutOfRange {
+                line,
+                value,
+                min,
+                max,
+            });
+        }
+        *val = value;
+    }
+    Ok(res)
+}
+
+const U8: (i64, i64) = (0, 255);
+
+fn parse_chunk(line: usize, mnemonic: &str, args: &[&str]) -> Result<Chunk, AsmError> {
+    let chunk = match mnemonic {
+        "RGB" => {
+            let [r, g, b] = parse_args(line, args, [U8; 3])?;
+            Chunk::RGB([r as u8, g as u8, b as u8])
+        }
+        "RGBA" => {
+            let [r, g, b, a] = parse_args(line, args, [U8; 4])?;
+            Chunk::RGBA([r as u8, g as u8, b as u8, a as u8])
+        }
+        "INDEX" => {
+            let [index] = parse_args(line, args, [(0, PREV_ARR_SIZE as i64 - 1)])?;
+            Chunk::INDEX(index as u8)
+        }
+        "DIFF" => {
+            let [dr, dg, db] = parse_args(line, args, [(-2, 1); 3])?;
+            Chunk::DIFF {
+                dr: dr as i8,
+                dg: dg as i8,
+                db: db as i8,
+            }
+        }
+        "LUMA" => {
+            let [dg, dr_dg, db_dg] = parse_args(line, args, [(-32, 31), (-8, 7), (-8, 7)])?;
+            Chunk::LUMA {
+                dg: dg as i8,
+                dr_dg: dr_dg as i8,
+                db_dg: db_dg as i8,
+            }
+        }
+        "RUN" => {
+            let [length] = parse_args(line, args, [(1, 62)])?;
+            Chunk::RUN(length as u8)
+        }
+        _ => {
+            return Err(UnknownMnemonic {
+                line,
+                mnemonic: mnemonic.to_string(),
+            })
+        }
+    };
+    Ok(chunk)
+}
+
+/// Assembles a stream from one instruction per line, `#` starts a comment:
+/// ```text
+/// HEADER 2 2 4 0     # width height channels colorspace
+/// RGBA 255 0 0 255
+/// DIFF -1 0 1        # dr dg db
+/// LUMA -3 1 2        # dg dr-dg db-dg
+/// INDEX 5
+/// RUN 12
+/// ```
+/// Mnemonics are case-insensitive & may carry the `QOI_OP_` prefix. \
+/// The header & chunks aren't checked against each other, so invalid streams can be written on purpose.
+pub fn assemble(text: &str) -> Result<Vec<u8>, AsmError> {
+    let mut out = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line_nr = i + 1;
+        let code = line.split('#').next().unwrap();
+        let mut words = code.split_whitespace();
+        let Some(mnemonic) = words.next() else {
+            continue;
+        };
+        let mnemonic = mnemonic.to_ascii_uppercase();
+        let mnemonic = mnemonic.trim_start_matches("QOI_OP_");
+        let args: Vec<&str> = words.collect();
+
+        if out.is_empty() {
+            if mnemonic != "HEADER" {
+                return Err(MissingHeader);
+            }
+            let [width, height, channels, colorspace] = parse_args(
+                line_nr,
+                &args,
+                [(0, u32::MAX as i64), (0, u32::MAX as i64), U8, U8],
+            )?;
+            out.extend_from_slice(&MAGIC);
+            out.extend_from_slice(&(width as u32).to_be_bytes());
+            out.extend_from_slice(&(height as u32).to_be_bytes());
+            out.push(channels as u8);
+            out.push(colorspace as u8);
+            continue;
+        }
+        out.extend(parse_chunk(line_nr, mnemonic, &args)?.to_bytes());
+    }
+    if out.is_empty() {
+        return Err(MissingHeader);
+    }
+    out.extend_from_slice(&STREAM_END);
+    Ok(out)
+}
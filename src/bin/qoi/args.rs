@@ -0,0 +1,106 @@
+use crate::CliError;
+use qoi::{raw::RawLayout, resize::Filter, ColorChannel, ColorSpace};
+use std::{collections::HashMap, str::FromStr};
+
+/// Positional arguments and `--name value` options.
+pub struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    /// Every argument starting with `--` takes a value.
+    pub fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut res = Args {
+            positional: Vec::new(),
+            options: HashMap::new(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                None => res.positional.push(arg.clone()),
+                Some(name) => match iter.next() {
+                    None => return Err(CliError::Usage(format!("Missing value for --{}", name))),
+                    Some(value) => {
+                        res.options.insert(name.to_string(), value.clone());
+                    }
+                },
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn positional<const N: usize>(&self) -> Result<[&str; N], CliError> {
+        let args: Vec<&str> = self.positional.iter().map(String::as_str).collect();
+        args.try_into().map_err(|args: Vec<&str>| {
+            CliError::Usage(format!("Expected {} arguments, received {}", N, args.len()))
+        })
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        match self.options.get(name) {
+            None => Ok(None),
+            Some(value) => match value.parse() {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(CliError::Usage(format!(
+                    "Invalid value for --{}: {}",
+                    name, value
+                ))),
+            },
+        }
+    }
+
+    pub fn required<T: FromStr>(&self, name: &str) -> Result<T, CliError> {
+        self.get(name)?
+            .ok_or_else(|| CliError::Usage(format!("Missing option --{}", name)))
+    }
+
+    pub fn channels(&self) -> Result<Option<ColorChannel>, CliError> {
+        match self.get::<u8>("channels")? {
+            None => Ok(None),
+            Some(channels) => ColorChannel::try_from(channels).map(Some).map_err(|_| {
+                CliError::Usage(format!("Invalid value for --channels: {}", channels))
+            }),
+        }
+    }
+
+    pub fn colorspace(&self) -> Result<Option<ColorSpace>, CliError> {
+        match self.options.get("colorspace").map(String::as_str) {
+            None => Ok(None),
+            Some("srgb" | "0") => Ok(Some(ColorSpace::SRGB)),
+            Some("linear" | "1") => Ok(Some(ColorSpace::LINEAR)),
+            Some(value) => Err(CliError::Usage(format!(
+                "Invalid value for --colorspace: {}",
+                value
+            ))),
+        }
+    }
+
+    pub fn layout(&self) -> Result<Option<RawLayout>, CliError> {
+        match self.options.get("layout").map(String::as_str) {
+            None => Ok(None),
+            Some("rgba" | "rgb") => Ok(Some(RawLayout::RGBA)),
+            Some("bgra" | "bgr") => Ok(Some(RawLayout::BGRA)),
+            Some("argb") => Ok(Some(RawLayout::ARGB)),
+            Some("abgr") => Ok(Some(RawLayout::ABGR)),
+            Some(value) => Err(CliError::Usage(format!(
+                "Invalid value for --layout: {}",
+                value
+            ))),
+        }
+    }
+
+    pub fn filter(&self) -> Result<Option<Filter>, CliError> {
+        match self.options.get("filter").map(String::as_str) {
+            None => Ok(None),
+            Some("nearest") => Ok(Some(Filter::Nearest)),
+            Some("bilinear") => Ok(Some(Filter::Bilinear)),
+            Some("box") => Ok(Some(Filter::Box)),
+            Some("lanczos3") => Ok(Some(Filter::Lanczos3)),
+            Some(value) => Err(CliError::Usage(format!(
+                "Invalid value for --filter: {}",
+                value
+            ))),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{args::Args, read_image, write_image, CliError, INPUT_FORMATS, OUTPUT_FORMATS};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+    format: String,
+    // Another input maps to the same output, e.g. a.png & a.bmp
+    duplicate: bool,
+}
+
+enum Outcome {
+    Converted { input_size: u64, output_size: u64 },
+    Skipped,
+    Failed(String),
+}
+
+#[derive(Default)]
+struct Summary {
+    files: usize,
+    converted: usize,
+    skipped: usize,
+    failed: usize,
+    input_size: u64,
+    output_size: u64,
+}
+
+impl Summary {
+    fn add(&mut self, outcome: &Outcome) {
+        self.files += 1;
+        match outcome {
+            Outcome::Converted {
+                input_size,
+                output_size,
+            } => {
+                self.converted += 1;
+                self.input_size += input_size;
+                self.output_size += output_size;
+            }
+            Outcome::Skipped => self.skipped += 1,
+            Outcome::Failed(_) => self.failed += 1,
+        }
+    }
+
+    fn print(&self, name: &str) {
+        let ratio = match self.input_size {
+            0 => "-".to_string(),
+            size => format!("{:.1}%", self.output_size as f64 / size as f64 * 100.0),
+        };
+        println!(
+            "{:<8} {:>7} {:>10} {:>8} {:>7} {:>14} {:>14} {:>7}",
+            name,
+            self.files,
+            self.converted,
+            self.skipped,
+            self.failed,
+            self.input_size,
+            self.output_size,
+            ratio
+        );
+    }
+}
+
+// Collects the files of `dir` & its subdirectories with one of the `inputs` extensions
+fn collect_jobs(
+    dir: &Path,
+    in_root: &Path,
+    out_root: &Path,
+    inputs: &[&str],
+    to: &str,
+    jobs: &mut Vec<Job>,
+) -> Result<(), CliError> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            // The output directory may be inside of the input directory
+            if path != out_root {
+                collect_jobs(&path, in_root, out_root, inputs, to, jobs)?;
+            }
+            continue;
+        }
+        let format = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if inputs.contains(&ext.to_ascii_lowercase().as_str()) => {
+                ext.to_ascii_lowercase()
+            }
+            _ => continue,
+        };
+        let relative = path.strip_prefix(in_root).unwrap();
+        let output = out_root.join(relative).with_extension(to);
+        jobs.push(Job {
+            input: path,
+            output,
+            format,
+            duplicate: false,
+        });
+    }
+    Ok(())
+}
+
+// The output is up-to-date, if it's at least as new as the input
+fn is_up_to_date(job: &Job) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified());
+    match (modified(&job.input), modified(&job.output)) {
+        (Ok(input), Ok(output)) => output >= input,
+        _ => false,
+    }
+}
+
+fn run_job(job: &Job) -> Outcome {
+    if job.duplicate {
+        return Outcome::Failed(format!(
+            "{} is the output of another input",
+            job.output.display()
+        ));
+    }
+    if is_up_to_date(job) {
+        return Outcome::Skipped;
+    }
+    let res = || -> Result<Outcome, CliError> {
+        if let Some(parent) = job.output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let (header, mut pixels) = read_image(&job.input)?;
+        let output_size = write_image(&job.output, &header, &mut pixels)? as u64;
+        Ok(Outcome::Converted {
+            input_size: fs::metadata(&job.input)?.len(),
+            output_size,
+        })
+    };
+    match res() {
+        Ok(outcome) => outcome,
+        Err(err) => Outcome::Failed(format!("{:?}", err)),
+    }
+}
+
+// Every worker takes the next job, until none are left
+fn run_jobs(jobs: &[Job], threads: usize) -> Vec<Outcome> {
+    let next = AtomicUsize::new(0);
+    let mut outcomes: Vec<(usize, Outcome)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut outcomes = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        match jobs.get(idx) {
+                            None => return outcomes,
+                            Some(job) => outcomes.push((idx, run_job(job))),
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    outcomes.sort_by_key(|(idx, _)| *idx);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+pub fn batch(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [in_dir, out_dir] = args.positional()?;
+    let to: String = args.get("to")?.unwrap_or_else(|| "qoi".to_string());
+    let to = to.trim_start_matches('.').to_ascii_lowercase();
+    if !OUTPUT_FORMATS.contains(&to.as_str()) {
+        return Err(CliError::Usage(format!(
+            "Unsupported output format .{}",
+            to
+        )));
+    }
+    let threads = match args.get::<usize>("threads")? {
+        None => thread::available_parallelism().map_or(1, |threads| threads.get()),
+        Some(0) => {
+            return Err(CliError::Usage(
+                "--threads needs to be at least 1".to_string(),
+            ))
+        }
+        Some(threads) => threads,
+    };
+
+    // Everything is converted to QOI by default, or from QOI to another format
+    let inputs: Vec<&str> = match to.as_str() {
+        "qoi" => INPUT_FORMATS
+            .iter()
+            .copied()
+            .filter(|&ext| ext != "qoi")
+            .collect(),
+        _ => vec!["qoi"],
+    };
+    let (in_root, out_root) = (Path::new(in_dir), Path::new(out_dir));
+    let mut jobs = Vec::new();
+    collect_jobs(in_root, in_root, out_root, &inputs, &to, &mut jobs)?;
+    let mut outputs = HashSet::new();
+    for job in &mut jobs {
+        job.duplicate = !outputs.insert(job.output.clone());
+    }
+    let outcomes = run_jobs(&jobs, threads);
+
+    let mut total = Summary::default();
+    let mut formats: BTreeMap<&str, Summary> = BTreeMap::new();
+    for (job, outcome) in jobs.iter().zip(&outcomes) {
+        total.add(outcome);
+        formats.entry(&job.format).or_default().add(outcome);
+    }
+    println!(
+        "{:<8} {:>7} {:>10} {:>8} {:>7} {:>14} {:>14} {:>7}",
+        "format", "files", "converted", "skipped", "failed", "input bytes", "output bytes", "ratio"
+    );
+    for (format, summary) in &formats {
+        summary.print(format);
+    }
+    total.print("total");
+
+    for (job, outcome) in jobs.iter().zip(&outcomes) {
+        if let Outcome::Failed(err) = outcome {
+            println!("failed: {}: {}", job.input.display(), err);
+        }
+    }
+    Ok(if total.failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    })
+}
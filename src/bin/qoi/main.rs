@@ -0,0 +1,527 @@
+mod args;
+mod batch;
+
+use args::*;
+use qoi::{
+    asm, bmp, color, compare, crop, decode, disasm, farbfeld, png, pnm, raw, resize, stats, tga,
+    ColorChannel, ColorSpace, Header, Opcode, Pixel,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+const USAGE: &str = "Usage: qoi <command> [options]
+Commands:
+    info <file.qoi> ............................ print the header, compressed size & ratio
+    validate <file.qoi> ........................ check strict conformance to the spec
+                                                 exit code 0 = valid, 1 = invalid, 2 = error
+    stats <file.qoi> ........................... print how often each opcode is used, the run lengths
+                                                 & how well the index is hit
+    disasm <file.qoi> .......................... print one line per chunk: offset, bytes, opcode, arguments
+                                                 & pixel coordinates, exit code 1 if the stream is invalid
+    asm <in.txt> <out.qoi> ..................... assemble a stream from one instruction per line, e.g.
+                                                 HEADER 2 1 4 0, RGBA 255 0 0 255, RGB 0 0 255, DIFF -1 0 1,
+                                                 LUMA -3 1 2, INDEX 5 & RUN 12, # starts a comment
+    encode <in.raw> <out.qoi> --width <w> --height <h> [--channels 3|4] [--colorspace srgb|linear] [--layout <layout>]
+                                                 encode a headerless dump, 4 channels by default
+    decode <in.qoi> <out.raw> [--channels 3|4] [--layout <layout>]
+                                                 decode into a headerless dump, 4 channels by default
+                                                 layouts: rgba (default), bgra, argb & abgr
+    convert <in> <out> [--colorspace srgb|linear]
+                                                 convert between formats, chosen by the file extensions
+                                                 supported: .qoi, .png, .bmp, .tga (written with RLE), .ff,
+                                                 .pgm, .ppm, .pam & .pnm (input only)
+                                                 --colorspace converts the r, g & b channels, alpha stays linear
+    crop <in.qoi> <out.qoi> --x <x> --y <y> --width <w> --height <h>
+                                                 crop a region, streaming the input instead of decoding it whole
+    resize <in> <out> [--width <w>] [--height <h>] [--filter nearest|bilinear|box|lanczos3]
+                                                 resize an image of any supported format, lanczos3 by default
+                                                 if only one dimension is given, the aspect ratio is kept
+    diff <a> <b> [--out <diff>] ................ compare two images of any supported format
+                                                 --out writes an image, that highlights differing pixels red
+                                                 exit code 0 = identical, 1 = different, 2 = error
+    batch <in-dir> <out-dir> [--to <ext>] [--threads <n>]
+                                                 convert a directory tree, keeping its structure
+                                                 all supported images to QOI by default,
+                                                 or QOI images to the format of --to
+                                                 up-to-date outputs are skipped, exit code 1 on failures
+Examples
+    qoi info imgs/testcard.qoi
+    qoi encode imgs/testcard.bin testcard.qoi --width 256 --height 256
+    qoi convert imgs/dice.png dice.qoi
+    qoi batch assets assets_qoi --threads 8";
+
+pub enum CliError {
+    Usage(String),
+    Decode(decode::DecodeError),
+    Encode(qoi::encode::EncodeError),
+    Png(png::PngError),
+    Pnm(pnm::PnmError),
+    Bmp(bmp::BmpError),
+    Tga(tga::TgaError),
+    Farbfeld(farbfeld::FarbfeldError),
+    Raw(raw::RawError),
+    Compare(compare::CompareError),
+    Asm(asm::AsmError),
+    Crop(crop::CropError),
+    Resize(resize::ResizeError),
+    Color(color::ColorError),
+    IOError(io::Error),
+}
+
+impl std::fmt::Debug for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Usage(msg) => write!(f, "{}\n\n{}", msg, USAGE),
+            Self::Decode(err) => write!(f, "{:?}", err),
+            Self::Encode(err) => write!(f, "{:?}", err),
+            Self::Png(err) => write!(f, "{:?}", err),
+            Self::Pnm(err) => write!(f, "{:?}", err),
+            Self::Bmp(err) => write!(f, "{:?}", err),
+            Self::Tga(err) => write!(f, "{:?}", err),
+            Self::Farbfeld(err) => write!(f, "{:?}", err),
+            Self::Raw(err) => write!(f, "{:?}", err),
+            Self::Compare(err) => write!(f, "{:?}", err),
+            Self::Asm(err) => write!(f, "{:?}", err),
+            Self::Crop(err) => write!(f, "{:?}", err),
+            Self::Resize(err) => write!(f, "{:?}", err),
+            Self::Color(err) => write!(f, "{:?}", err),
+            Self::IOError(err) => write!(f, "IO Error: {}", err),
+        }
+    }
+}
+
+impl From<decode::DecodeError> for CliError {
+    fn from(value: decode::DecodeError) -> Self {
+        CliError::Decode(value)
+    }
+}
+
+impl From<qoi::encode::EncodeError> for CliError {
+    fn from(value: qoi::encode::EncodeError) -> Self {
+        CliError::Encode(value)
+    }
+}
+
+impl From<png::PngError> for CliError {
+    fn from(value: png::PngError) -> Self {
+        CliError::Png(value)
+    }
+}
+
+impl From<pnm::PnmError> for CliError {
+    fn from(value: pnm::PnmError) -> Self {
+        CliError::Pnm(value)
+    }
+}
+
+impl From<bmp::BmpError> for CliError {
+    fn from(value: bmp::BmpError) -> Self {
+        CliError::Bmp(value)
+    }
+}
+
+impl From<tga::TgaError> for CliError {
+    fn from(value: tga::TgaError) -> Self {
+        CliError::Tga(value)
+    }
+}
+
+impl From<farbfeld::FarbfeldError> for CliError {
+    fn from(value: farbfeld::FarbfeldError) -> Self {
+        CliError::Farbfeld(value)
+    }
+}
+
+impl From<raw::RawError> for CliError {
+    fn from(value: raw::RawError) -> Self {
+        CliError::Raw(value)
+    }
+}
+
+impl From<compare::CompareError> for CliError {
+    fn from(value: compare::CompareError) -> Self {
+        CliError::Compare(value)
+    }
+}
+
+impl From<asm::AsmError> for CliError {
+    fn from(value: asm::AsmError) -> Self {
+        CliError::Asm(value)
+    }
+}
+
+impl From<crop::CropError> for CliError {
+    fn from(value: crop::CropError) -> Self {
+        CliError::Crop(value)
+    }
+}
+
+impl From<resize::ResizeError> for CliError {
+    fn from(value: resize::ResizeError) -> Self {
+        CliError::Resize(value)
+    }
+}
+
+impl From<color::ColorError> for CliError {
+    fn from(value: color::ColorError) -> Self {
+        CliError::Color(value)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(value: io::Error) -> Self {
+        CliError::IOError(value)
+    }
+}
+
+fn channels_name(channels: ColorChannel) -> &'static str {
+    match channels {
+        ColorChannel::RGB => "RGB",
+        ColorChannel::RGBA => "RGBA",
+    }
+}
+
+fn colorspace_name(colorspace: ColorSpace) -> &'static str {
+    match colorspace {
+        ColorSpace::SRGB => "sRGB with linear alpha",
+        ColorSpace::LINEAR => "linear",
+    }
+}
+
+fn info(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [path] = args.positional()?;
+    let buffer = fs::read(path)?;
+    let header = decode::decode_header(&buffer)?;
+    println!("{}", path);
+    println!("  dimensions: {}x{}", header.width, header.height);
+    println!(
+        "  channels:   {} ({})",
+        u8::from(header.channels),
+        channels_name(header.channels)
+    );
+    println!(
+        "  colorspace: {} ({})",
+        u8::from(header.colorspace),
+        colorspace_name(header.colorspace)
+    );
+    println!("  size:       {} bytes", buffer.len());
+    println!("  raw size:   {} bytes", header.pixel_len());
+    println!(
+        "  ratio:      {:.1}%",
+        buffer.len() as f64 / header.pixel_len() as f64 * 100.0
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [path] = args.positional()?;
+    let buffer = fs::read(path)?;
+    match decode::validate(&buffer) {
+        Ok(header) => {
+            println!("{}: valid ({}x{})", path, header.width, header.height);
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(err) => {
+            println!("{}: invalid: {:?}", path, err);
+            Ok(ExitCode::from(1))
+        }
+    }
+}
+
+fn stats(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [path] = args.positional()?;
+    let buffer = fs::read(path)?;
+    let stats = stats::analyze(&buffer)?;
+    println!(
+        "{}: {}x{}, {} chunks, {} bytes",
+        path,
+        stats.header.width,
+        stats.header.height,
+        stats.chunks(),
+        stats.size
+    );
+    println!(
+        "  {:<13} {:>9} {:>10} {:>7} {:>10}",
+        "opcode", "chunks", "bytes", "share", "pixels"
+    );
+    for opcode in Opcode::ALL {
+        let op = stats.opcode(opcode);
+        println!(
+            "  {:<13} {:>9} {:>10} {:>6.1}% {:>10}",
+            opcode.name(),
+            op.chunks,
+            op.bytes,
+            stats.byte_share(opcode) * 100.0,
+            op.pixels
+        );
+    }
+    println!("  index hits:   {:.1}%", stats.index_hit_rate() * 100.0);
+    println!("  collisions:   {:.1}%", stats.collision_rate() * 100.0);
+    println!("  run lengths:");
+    for (i, &runs) in stats.run_lengths.iter().enumerate() {
+        if runs > 0 {
+            println!("    {:>2}: {}", i + 1, runs);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn disasm(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [path] = args.positional()?;
+    let buffer = fs::read(path)?;
+    let res = disasm::disassemble(&buffer)?;
+    let header = res.header;
+    println!(
+        "{:08x}  header: {}x{}, {} channels, colorspace {}",
+        0,
+        header.width,
+        header.height,
+        u8::from(header.channels),
+        u8::from(header.colorspace)
+    );
+    // Large images have millions of chunks, so the lines are buffered
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    for line in &res.lines {
+        writeln!(out, "{}", line)?;
+    }
+    match res.error {
+        None => {
+            writeln!(out, "{:08x}  end marker", res.end)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(err) => {
+            writeln!(out, "{:08x}  error: {:?}", res.end, err)?;
+            Ok(ExitCode::from(1))
+        }
+    }
+}
+
+fn assemble(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [input, output] = args.positional()?;
+    let buffer = asm::assemble(&fs::read_to_string(input)?)?;
+    fs::write(output, &buffer)?;
+    println!("{} -> {} ({} bytes)", input, output, buffer.len());
+    Ok(ExitCode::SUCCESS)
+}
+
+fn encode(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [input, output] = args.positional()?;
+    let width = args.required("width")?;
+    let height = args.required("height")?;
+    let channels = args.channels()?.unwrap_or(ColorChannel::RGBA);
+    let colorspace = args.colorspace()?.unwrap_or(ColorSpace::SRGB);
+    let layout = args.layout()?.unwrap_or_default();
+    let header = Header::new(width, height, channels, colorspace);
+
+    let mut pixels = raw::read(input, &header, layout)?;
+    let size = qoi::write(output, &header, &mut pixels)?;
+    println!("{} -> {} ({} bytes)", input, output, size);
+    Ok(ExitCode::SUCCESS)
+}
+
+fn decode(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [input, output] = args.positional()?;
+    let channels = args.channels()?.unwrap_or(ColorChannel::RGBA);
+    let layout = args.layout()?.unwrap_or_default();
+
+    let (header, pixels) = qoi::read(input)?;
+    let header = Header { channels, ..header };
+    let size = raw::write(output, &header, &pixels, layout)?;
+    println!(
+        "{} -> {} ({}x{}, {} channels, {} bytes)",
+        input,
+        output,
+        header.width,
+        header.height,
+        u8::from(channels),
+        size
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Extensions of the formats `read_image` & `write_image` support.
+const INPUT_FORMATS: [&str; 9] = ["qoi", "png", "pgm", "ppm", "pam", "pnm", "bmp", "tga", "ff"];
+const OUTPUT_FORMATS: [&str; 8] = ["qoi", "png", "pgm", "ppm", "pam", "bmp", "tga", "ff"];
+
+fn extension(path: &Path) -> Result<String, CliError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        None => Err(CliError::Usage(format!(
+            "{} has no file extension",
+            path.display()
+        ))),
+        Some(ext) => Ok(ext.to_ascii_lowercase()),
+    }
+}
+
+fn read_image(path: &Path) -> Result<(Header, Vec<Pixel>), CliError> {
+    match extension(path)?.as_str() {
+        "qoi" => Ok(qoi::read(path)?),
+        "png" => Ok(png::read(path)?),
+        "pgm" | "ppm" | "pam" | "pnm" => Ok(pnm::read(path)?),
+        "bmp" => Ok(bmp::read(path)?),
+        "tga" => Ok(tga::read(path)?),
+        "ff" => Ok(farbfeld::read(path)?),
+        ext => Err(CliError::Usage(format!(
+            "Unsupported input format .{}",
+            ext
+        ))),
+    }
+}
+
+fn write_image(path: &Path, header: &Header, pixels: &mut [Pixel]) -> Result<usize, CliError> {
+    match extension(path)?.as_str() {
+        "qoi" => Ok(qoi::write(path, header, pixels)?),
+        "png" => Ok(png::write(path, header, pixels)?),
+        "pgm" => Ok(pnm::write(path, header, pixels, pnm::PnmFormat::Pgm)?),
+        "ppm" => Ok(pnm::write(path, header, pixels, pnm::PnmFormat::Ppm)?),
+        "pam" => Ok(pnm::write(path, header, pixels, pnm::PnmFormat::Pam)?),
+        "bmp" => Ok(bmp::write(path, header, pixels)?),
+        "tga" => Ok(tga::write(path, header, pixels, true)?),
+        "ff" => Ok(farbfeld::write(path, header, pixels)?),
+        ext => Err(CliError::Usage(format!(
+            "Unsupported output format .{}",
+            ext
+        ))),
+    }
+}
+
+fn convert(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [input, output] = args.positional()?;
+    let (mut header, mut pixels) = read_image(Path::new(input))?;
+    if let Some(colorspace) = args.colorspace()? {
+        color::convert_colorspace(&mut header, &mut pixels, colorspace)?;
+    }
+    let size = write_image(Path::new(output), &header, &mut pixels)?;
+    println!(
+        "{} -> {} ({}x{}, {} bytes)",
+        input, output, header.width, header.height, size
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn crop(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [input, output] = args.positional()?;
+    let x = args.required("x")?;
+    let y = args.required("y")?;
+    let width = args.required("width")?;
+    let height = args.required("height")?;
+
+    let size = crop::crop_file(input, output, x, y, width, height)?;
+    println!(
+        "{} -> {} ({}x{} at {}, {}, {} bytes)",
+        input, output, width, height, x, y, size
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn resize(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [input, output] = args.positional()?;
+    let filter = args.filter()?.unwrap_or_default();
+    let (header, pixels) = read_image(Path::new(input))?;
+
+    // A missing dimension keeps the aspect ratio
+    let scaled = |len: u32, from: u32, to: u32| {
+        ((len as u64 * to as u64 + from as u64 / 2) / from as u64).max(1) as u32
+    };
+    let (width, height) = match (args.get("width")?, args.get("height")?) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, scaled(header.height, header.width, width)),
+        (None, Some(height)) => (scaled(header.width, header.height, height), height),
+        (None, None) => {
+            return Err(CliError::Usage(
+                "Missing option --width or --height".to_string(),
+            ))
+        }
+    };
+
+    let (header, mut pixels) = resize::resize(&header, &pixels, width, height, filter)?;
+    let size = write_image(Path::new(output), &header, &mut pixels)?;
+    println!(
+        "{} -> {} ({}x{}, {} bytes)",
+        input, output, width, height, size
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(args: &[String]) -> Result<ExitCode, CliError> {
+    let args = Args::parse(args)?;
+    let [first, second] = args.positional()?;
+    let out: Option<String> = args.get("out")?;
+
+    let (header_a, pixels_a) = read_image(Path::new(first))?;
+    let (header_b, pixels_b) = read_image(Path::new(second))?;
+    let (a, b) = ((&header_a, &pixels_a[..]), (&header_b, &pixels_b[..]));
+    let res = compare::compare(a, b)?;
+    if let Some(out) = out {
+        let (header, mut pixels) = compare::diff_image(a, b)?;
+        write_image(Path::new(&out), &header, &mut pixels)?;
+    }
+
+    if res.is_identical() {
+        println!("{} and {} are identical", first, second);
+        return Ok(ExitCode::SUCCESS);
+    }
+    println!("{} and {} differ", first, second);
+    println!(
+        "  mismatches:     {} of {} pixels ({:.2}%)",
+        res.mismatches,
+        header_a.pixel_amount(),
+        res.mismatches as f64 / header_a.pixel_amount() as f64 * 100.0
+    );
+    if let Some((x, y)) = res.first_mismatch {
+        println!("  first mismatch: x {} y {}", x, y);
+    }
+    let [r, g, b, a] = res.max_error;
+    println!("  max error:      r {} g {} b {} a {}", r, g, b, a);
+    println!("  PSNR:           {:.2} dB", res.psnr);
+    Ok(ExitCode::from(1))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        None => (None, &args[..]),
+        Some((command, rest)) => (Some(command.as_str()), rest),
+    };
+    let res = match command {
+        Some("info") => info(rest),
+        Some("validate") => validate(rest),
+        Some("stats") => stats(rest),
+        Some("disasm") => disasm(rest),
+        Some("asm") => assemble(rest),
+        Some("encode") => encode(rest),
+        Some("decode") => decode(rest),
+        Some("convert") => convert(rest),
+        Some("crop") => crop(rest),
+        Some("resize") => resize(rest),
+        Some("diff") => diff(rest),
+        Some("batch") => batch::batch(rest),
+        Some("-h" | "--help" | "help") => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(command) => Err(CliError::Usage(format!("Unknown command {}", command))),
+        None => Err(CliError::Usage("Missing command".to_string())),
+    };
+    match res {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{:?}", err);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use crate::*;
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+pub const BMP_MAGIC: [u8; 2] = *b"BM";
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 40;
+const V4_HEADER_SIZE: usize = 108;
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+pub enum BmpError {
+    /// The data doesn't start with "BM".
+    InvalidMagic,
+
+    /// The data ended in the middle of the headers, palette or pixel data.
+    UnexpectedEnd,
+
+    /// The headers contain an unknown size, plane count, negative width or pixel data offset.
+    InvalidHeader,
+
+    /// The combination of bits per pixel & compression isn't supported.
+    UnsupportedFormat {
+        bits_per_pixel: u16,
+        compression: u32,
+    },
+
+    InvalidDimensions(DimensionsError),
+
+    /// A pixel refers to a palette entry that doesn't exist.
+    InvalidPaletteIndex(u8),
+
+    /// When encoding, there are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    IOError(std::io::Error),
+}
+
+impl fmt::Debug for BmpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => {
+                write!(
+                    f,
+                    "Invalid magic: The data doesn't start with {:?}.",
+                    BMP_MAGIC
+                )
+            }
+            Self::UnexpectedEnd => write!(f, "The BMP data ended unexpectedly."),
+            Self::InvalidHeader => write!(f, "The BMP headers are malformed."),
+            Self::UnsupportedFormat {
+                bits_per_pixel,
+                compression,
+            } => {
+                write!(
+                    f,
+                    "Unsupported format: {} bits per pixel with compression {}.",
+                    bits_per_pixel, compression
+                )
+            }
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::InvalidPaletteIndex(index) => {
+                write!(
+                    f,
+                    "Invalid palette index: {} is outside of the palette.",
+                    index
+                )
+            }
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+            Self::IOError(err) => write!(f, "IO Error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for BmpError {
+    fn from(value: std::io::Error) -> Self {
+        BmpError::IOError(value)
+    }
+}
+
+impl From<DimensionsError> for BmpError {
+    fn from(value: DimensionsError) -> Self {
+        BmpError::InvalidDimensions(value)
+    }
+}
+
+use BmpError::*;
+
+fn bytes<const N: usize>(data: &[u8], pos: usize) -> Result<[u8; N], BmpError> {
+    match data.get(pos..pos + N) {
+        None => Err(UnexpectedEnd),
+        Some(bytes) => Ok(bytes.try_into().unwrap()),
+    }
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Result<u16, BmpError> {
+    Ok(u16::from_le_bytes(bytes(data, pos)?))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<u32, BmpError> {
+    Ok(u32::from_le_bytes(bytes(data, pos)?))
+}
+
+// Extracts a channel from a pixel with a bit mask & scales it to 8 bits
+#[derive(Clone, Copy)]
+struct Mask {
+    mask: u32,
+    shift: u32,
+    max: u32,
+}
+
+impl Mask {
+    fn new(mask: u32) -> Self {
+        let shift = if mask == 0 { 0 } else { mask.trailing_zeros() };
+        let max = mask.checked_shr(shift).unwrap_or(0);
+        Mask { mask, shift, max }
+    }
+
+    fn extract(&self, val: u32) -> u8 {
+        match self.max {
+            0 => 255,
+            255 => ((val & self.mask) >> self.shift) as u8,
+            max => {
+                let val = ((val & self.mask) >> self.shift) as u64;
+                ((val * 255 + max as u64 / 2) / max as u64) as u8
+            }
+        }
+    }
+}
+
+struct BmpInfo {
+    width: i32,
+    height: i32,
+    bits_per_pixel: u16,
+    compression: u32,
+    masks: [Mask; 4],
+    palette: Vec<Pixel>,
+}
+
+fn parse_info(buffer: &[u8]) -> Result<BmpInfo, BmpError> {
+    let dib_size = u32_at(buffer, FILE_HEADER_SIZE)? as usize;
+    let dib = FILE_HEADER_SIZE;
+    let (width, height, planes, bits_per_pixel, compression, colors_used) = match dib_size {
+        // BITMAPCOREHEADER
+        12 => (
+            u16_at(buffer, dib + 4)? as i32,
+            u16_at(buffer, dib + 6)? as i32,
+            u16_at(buffer, dib + 8)?,
+            u16_at(buffer, dib + 10)?,
+            BI_RGB,
+            0,
+        ),
+        40 | 52 | 56 | 64 | 108 | 124 => (
+            u32_at(buffer, dib + 4)? as i32,
+            u32_at(buffer, dib + 8)? as i32,
+            u16_at(buffer, dib + 12)?,
+            u16_at(buffer, dib + 14)?,
+            u32_at(buffer, dib + 16)?,
+            u32_at(buffer, dib + 32)? as usize,
+        ),
+        _ => return Err(InvalidHeader),
+    };
+    if planes != 1 {
+        return Err(InvalidHeader);
+    }
+
+    let unsupported = UnsupportedFormat {
+        bits_per_pixel,
+        compression,
+    };
+    match (bits_per_pixel, compression) {
+        (1 | 4 | 8 | 16 | 24 | 32, BI_RGB)
+        | (16 | 32, BI_BITFIELDS | BI_ALPHABITFIELDS)
+        | (8, BI_RLE8)
+        | (4, BI_RLE4) => {}
+        _ => return Err(unsupported),
+    }
+
+    // Masks directly follow a BITMAPINFOHEADER & are part of the later versions
+    let masks = match (bits_per_pixel, compression) {
+        (_, BI_BITFIELDS | BI_ALPHABITFIELDS) => {
+            let has_alpha = compression == BI_ALPHABITFIELDS || dib_size >= 56;
+            let mask = |i: usize| u32_at(buffer, dib + INFO_HEADER_SIZE + 4 * i);
+            let alpha = if has_alpha { mask(3)? } else { 0 };
+            [mask(0)?, mask(1)?, mask(2)?, alpha]
+        }
+        (16, _) => [0x7c00, 0x03e0, 0x001f, 0],
+        (32, _) => [0x00ff0000, 0x0000ff00, 0x000000ff, 0xff000000],
+        _ => [0; 4],
+    }
+    .map(Mask::new);
+
+    let mut palette = Vec::new();
+    if bits_per_pixel <= 8 {
+        let colors = match colors_used {
+            0 => 1 << bits_per_pixel,
+            colors => colors.min(1 << bits_per_pixel),
+        };
+        let entry_size = if dib_size == 12 { 3 } else { 4 };
+        let start = dib + dib_size;
+        for i in 0..colors {
+            let [b, g, r] = bytes(buffer, start + i * entry_size)?;
+            palette.push(Pixel::from([r, g, b]));
+        }
+    }
+
+    Ok(BmpInfo {
+        width,
+        height,
+        bits_per_pixel,
+        compression,
+        masks,
+        palette,
+    })
+}
+
+// Decodes RLE8 & RLE4 data into palette indices, bottom-up
+fn decode_rle(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    four_bit: bool,
+) -> Result<Vec<u8>, BmpError> {
+    // Pixels which are skipped by a delta or end of line keep index 0
+    let mut indices = vec![0u8; width * height];
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut pos = 0;
+    let mut put = |x: &mut usize, y: usize, idx: u8| {
+        if *x < width && y < height {
+            indices[y * width + *x] = idx;
+        }
+        *x += 1;
+    };
+    loop {
+        let [count, val] = bytes(data, pos)?;
+        pos += 2;
+        match (count, val) {
+            (0, 0) => {
+                x = 0;
+                y += 1;
+            }
+            (0, 1) => break,
+            (0, 2) => {
+                let [dx, dy] = bytes(data, pos)?;
+                pos += 2;
+                x += dx as usize;
+                y += dy as usize;
+            }
+            (0, amount) => {
+                let amount = amount as usize;
+                let size = if four_bit { amount.div_ceil(2) } else { amount };
+                let run = data.get(pos..pos + size).ok_or(UnexpectedEnd)?;
+                for i in 0..amount {
+                    let idx = match four_bit {
+                        true => (run[i / 2] >> (4 - 4 * (i % 2))) & 0xf,
+                        false => run[i],
+                    };
+                    put(&mut x, y, idx);
+                }
+                // Absolute runs are padded to 16 bits
+                pos += size + size % 2;
+            }
+            (count, val) => {
+                for i in 0..count as usize {
+                    let idx = match four_bit {
+                        true => (val >> (4 - 4 * (i % 2))) & 0xf,
+                        false => val,
+                    };
+                    put(&mut x, y, idx);
+                }
+            }
+        }
+        if y >= height {
+            break;
+        }
+    }
+    Ok(indices)
+}
+
+/// Decodes a BMP image with 1, 4, 8, 16, 24 or 32 bits per pixel, including RLE8, RLE4 & bit fields. \
+/// Images with an alpha channel are returned as RGBA, others as RGB. \
+/// 32-bit images without alpha mask are treated as RGBA, unless all alpha values are 0.
+pub fn decode(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), BmpError> {
+    if !buffer.starts_with(&BMP_MAGIC) {
+        return Err(InvalidMagic);
+    }
+    let offset = u32_at(buffer, 10)? as usize;
+    let info = parse_info(buffer)?;
+    let data = buffer.get(offset..).ok_or(InvalidHeader)?;
+
+    // Positive heights are stored bottom-up, negative ones top-down
+    let top_down = info.height < 0;
+    let width = u32::try_from(info.width).map_err(|_| InvalidHeader)?;
+    let height = info.height.unsigned_abs();
+    let mut header = Header::new(width, height, ColorChannel::RGB, ColorSpace::SRGB);
+    header.check_dimensions()?;
+    let (width, height) = (width as usize, height as usize);
+    let row = |y: usize| if top_down { y } else { height - 1 - y };
+    let palette = |idx: u8| {
+        info.palette
+            .get(idx as usize)
+            .copied()
+            .ok_or(InvalidPaletteIndex(idx))
+    };
+
+    if matches!(info.compression, BI_RLE8 | BI_RLE4) {
+        let indices = decode_rle(data, width, height, info.compression == BI_RLE4)?;
+        let mut pixels = vec![Pixel::def(); width * height];
+        for (y, line) in indices.chunks(width).enumerate() {
+            let start = row(y) * width;
+            for (px, &idx) in pixels[start..start + width].iter_mut().zip(line) {
+                *px = palette(idx)?;
+            }
+        }
+        return Ok((header, pixels));
+    }
+
+    let bits = info.bits_per_pixel as usize;
+    let stride = (width * bits).div_ceil(32) * 4;
+    // Checked before allocating, so a tiny file can't claim a huge image
+    let data = data.get(..stride * height).ok_or(UnexpectedEnd)?;
+    let [r, g, b, a] = info.masks;
+    let mut pixels = vec![Pixel::def(); width * height];
+    for (y, line) in data.chunks_exact(stride).enumerate() {
+        let start = row(y) * width;
+        for (x, px) in pixels[start..start + width].iter_mut().enumerate() {
+            *px = match bits {
+                1 | 4 | 8 => {
+                    let bit = x * bits;
+                    let idx = (line[bit / 8] >> (8 - bits - bit % 8)) & (0xff >> (8 - bits));
+                    palette(idx)?
+                }
+                24 => Pixel::from([line[3 * x + 2], line[3 * x + 1], line[3 * x]]),
+                _ => {
+                    let val = match bits {
+                        16 => u16::from_le_bytes([line[2 * x], line[2 * x + 1]]) as u32,
+                        _ => u32::from_le_bytes(line[4 * x..4 * x + 4].try_into().unwrap()),
+                    };
+                    Pixel {
+                        r: r.extract(val),
+                        g: g.extract(val),
+                        b: b.extract(val),
+                        a: a.extract(val),
+                    }
+                }
+            };
+        }
+    }
+
+    if a.mask != 0 {
+        // Many writers leave the unused fourth byte at 0, instead of using it as alpha
+        if pixels.iter().all(|px| px.a == 0) {
+            pixels.iter_mut().for_each(|px| px.a = 255);
+        } else {
+            header.channels = ColorChannel::RGBA;
+        }
+    }
+    Ok((header, pixels))
+}
+
+/// Encodes a bottom-up BMP image. \
+/// RGB images are stored with 24 bits per pixel, RGBA images with 32 bits & a `BITMAPV4HEADER` alpha mask.
+pub fn encode(header: &Header, pixels: &[Pixel]) -> Result<Vec<u8>, BmpError> {
+    // Valid dimensions always fit into the signed 32-bit fields, as `PIXELS_MAX < i32::MAX`
+    header.check_dimensions()?;
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+
+    let (dib_size, bits) = match header.channels {
+        ColorChannel::RGB => (INFO_HEADER_SIZE, 24),
+        ColorChannel::RGBA => (V4_HEADER_SIZE, 32),
+    };
+    let width = header.width as usize;
+    let stride = (width * bits).div_ceil(32) * 4;
+    let offset = FILE_HEADER_SIZE + dib_size;
+    let image_size = stride * header.height as usize;
+
+    let mut out = Vec::with_capacity(offset + image_size);
+    out.extend_from_slice(&BMP_MAGIC);
+    out.extend_from_slice(&((offset + image_size) as u32).to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(offset as u32).to_le_bytes());
+
+    out.extend_from_slice(&(dib_size as u32).to_le_bytes());
+    out.extend_from_slice(&header.width.to_le_bytes());
+    out.extend_from_slice(&header.height.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&(bits as u16).to_le_bytes());
+    let compression = if bits == 32 { BI_BITFIELDS } else { BI_RGB };
+    out.extend_from_slice(&compression.to_le_bytes());
+    out.extend_from_slice(&(image_size as u32).to_le_bytes());
+    // 72 DPI resolution, no palette
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&[0; 8]);
+    if dib_size == V4_HEADER_SIZE {
+        for mask in [0x00ff0000u32, 0x0000ff00, 0x000000ff, 0xff000000] {
+            out.extend_from_slice(&mask.to_le_bytes());
+        }
+        out.extend_from_slice(b"BGRs");
+        // Endpoints & gamma are unused for sRGB
+        out.extend_from_slice(&[0; 48]);
+    }
+
+    for line in pixels.chunks(width).rev() {
+        let start = out.len();
+        for px in line {
+            match bits {
+                24 => out.extend_from_slice(&[px.b, px.g, px.r]),
+                _ => out.extend_from_slice(&[px.b, px.g, px.r, px.a]),
+            }
+        }
+        out.resize(start + stride, 0);
+    }
+    Ok(out)
+}
+
+pub fn read<P>(filepath: P) -> Result<(Header, Vec<Pixel>), BmpError>
+where
+    P: AsRef<Path>,
+{
+    decode(&fs::read(filepath)?)
+}
+
+pub fn write<P>(filepath: P, header: &Header, pixels: &[Pixel]) -> Result<usize, BmpError>
+where
+    P: AsRef<Path>,
+{
+    let buffer = encode(header, pixels)?;
+    let mut file: File = open_file_w(filepath)?;
+    file.write_all(&buffer)?;
+    Ok(buffer.len())
+}
@@ -12,6 +12,9 @@ pub trait BufIterType<T: Copy> {
     fn len(&self) -> usize {
         self.buf().len()
     }
+    fn is_empty(&self) -> bool {
+        self.buf().is_empty()
+    }
 }
 
 pub trait MutBufIterType<T: Copy>: BufIterType<T> {
@@ -21,28 +24,30 @@ pub trait MutBufIterType<T: Copy>: BufIterType<T> {
 pub trait SliceIter<T: Copy>: BufIterType<T> {
     fn slide(&mut self, backward: usize, forward: usize) -> Option<&[T]> {
         let (idx, buf, _) = self.half_mut();
-        let res = buf.get(*idx - backward..*idx - backward + forward);
+        let start = idx.checked_sub(backward)?;
+        let res = buf.get(start..start.checked_add(forward)?);
         if res.is_some() {
-            *idx += forward - backward;
+            *idx = start + forward;
         }
         res
     }
 
     fn slide_defered(&mut self, backward: usize, forward: usize) -> Option<&[T]> {
         let (idx, buf, defered) = self.half_mut();
-        let res = buf.get(*idx - backward..*idx - backward + forward);
+        let start = idx.checked_sub(backward)?;
+        let res = buf.get(start..start.checked_add(forward)?);
         if res.is_some() {
-            *defered += forward - backward;
+            *defered = (*defered + start + forward).checked_sub(*idx)?;
         }
         res
     }
 
     fn look_forward(&self, step: usize) -> Option<&[T]> {
-        self.buf().get(self.idx()..self.idx() + step)
+        self.buf().get(self.idx()..self.idx().checked_add(step)?)
     }
 
     fn look_backward(&self, step: usize) -> Option<&[T]> {
-        self.buf().get(self.idx() - step..self.idx())
+        self.buf().get(self.idx().checked_sub(step)?..self.idx())
     }
 
     fn step_forward(&mut self, step: usize) -> Option<&[T]> {
@@ -84,7 +89,7 @@ pub trait SliceIter<T: Copy>: BufIterType<T> {
 
     fn step_one_backward(&mut self) -> Option<&T> {
         let (idx, buf, _) = self.half_mut();
-        let res = buf.get(*idx - 1);
+        let res = buf.get(idx.checked_sub(1)?);
         if res.is_some() {
             *idx -= 1;
         }
@@ -95,30 +100,32 @@ pub trait SliceIter<T: Copy>: BufIterType<T> {
 pub trait SliceIterMut<T: Copy>: SliceIter<T> + MutBufIterType<T> {
     fn slide_mut(&mut self, backward: usize, forward: usize) -> Option<&mut [T]> {
         let (idx, buf, _) = self.full_mut();
-        let res = buf.get_mut(*idx - backward..*idx - backward + forward);
+        let start = idx.checked_sub(backward)?;
+        let res = buf.get_mut(start..start.checked_add(forward)?);
         if res.is_some() {
-            *idx += forward - backward;
+            *idx = start + forward;
         }
         res
     }
 
     fn slide_mut_defered(&mut self, backward: usize, forward: usize) -> Option<&mut [T]> {
         let (idx, buf, defered) = self.full_mut();
-        let res = buf.get_mut(*idx - backward..*idx - backward + forward);
+        let start = idx.checked_sub(backward)?;
+        let res = buf.get_mut(start..start.checked_add(forward)?);
         if res.is_some() {
-            *defered += forward - backward;
+            *defered = (*defered + start + forward).checked_sub(*idx)?;
         }
         res
     }
 
     fn look_forward_mut(&mut self, step: usize) -> Option<&mut [T]> {
         let (idx, buf, _) = self.full_mut();
-        buf.get_mut(*idx..*idx + step)
+        buf.get_mut(*idx..idx.checked_add(step)?)
     }
 
     fn look_backward_mut(&mut self, step: usize) -> Option<&mut [T]> {
         let (idx, buf, _) = self.full_mut();
-        buf.get_mut(*idx - step..*idx)
+        buf.get_mut(idx.checked_sub(step)?..*idx)
     }
 
     fn step_forward_mut(&mut self, step: usize) -> Option<&mut [T]> {
@@ -179,7 +186,7 @@ pub trait SliceIterMut<T: Copy>: SliceIter<T> + MutBufIterType<T> {
 
     fn set_current(&mut self, val: T) -> Option<&mut T> {
         let (idx, buf, _) = self.full_mut();
-        if *idx <= 0 {
+        if *idx == 0 {
             None
         } else {
             buf[*idx - 1] = val;
@@ -266,7 +273,7 @@ impl<'a, T: Copy> MutBufIter<'a, T> {
 
 impl<'a, T: Copy> BufIterType<T> for MutBufIter<'a, T> {
     fn buf(&self) -> &[T] {
-        &self.buf
+        self.buf
     }
     fn idx(&self) -> usize {
         self.idx
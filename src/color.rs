@@ -0,0 +1,143 @@
+use crate::*;
+use std::{fmt, sync::OnceLock};
+
+/// Decodes an sRGB encoded value between 0 & 1 to linear light.
+pub fn srgb_to_linear_f32(v: f32) -> f32 {
+    match v <= 0.04045 {
+        true => v / 12.92,
+        false => ((v + 0.055) / 1.055).powf(2.4),
+    }
+}
+
+/// Encodes a linear value between 0 & 1 with the sRGB transfer function.
+pub fn linear_to_srgb_f32(v: f32) -> f32 {
+    match v <= 0.0031308 {
+        true => v * 12.92,
+        false => 1.055 * v.powf(1.0 / 2.4) - 0.055,
+    }
+}
+
+/// Linear values between 0 & 1 of all 8-bit sRGB values.
+pub fn srgb_to_linear_table() -> &'static [f32; 256] {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| std::array::from_fn(|v| srgb_to_linear_f32(v as f32 / 255.0)))
+}
+
+fn to_u8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Converts an 8-bit sRGB value to the nearest 8-bit linear value. \
+/// This loses precision in dark tones, e.g. the sRGB values 0 to 12 all map to 0.
+pub fn srgb_to_linear(v: u8) -> u8 {
+    static TABLE: OnceLock<[u8; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| srgb_to_linear_table().map(to_u8))[v as usize]
+}
+
+/// Converts an 8-bit linear value to the nearest 8-bit sRGB value.
+pub fn linear_to_srgb(v: u8) -> u8 {
+    static TABLE: OnceLock<[u8; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| std::array::from_fn(|v| to_u8(linear_to_srgb_f32(v as f32 / 255.0))))
+        [v as usize]
+}
+
+pub enum ColorError {
+    /// There are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+}
+
+impl fmt::Debug for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+        }
+    }
+}
+
+use ColorError::*;
+
+/// Converts the r, g & b channels from sRGB to linear, alpha is linear in both & stays as it is.
+pub fn pixels_to_linear(pixels: &mut [Pixel]) {
+    for px in pixels {
+        px.r = srgb_to_linear(px.r);
+        px.g = srgb_to_linear(px.g);
+        px.b = srgb_to_linear(px.b);
+    }
+}
+
+/// Converts the r, g & b channels from linear to sRGB, alpha stays as it is.
+pub fn pixels_to_srgb(pixels: &mut [Pixel]) {
+    for px in pixels {
+        px.r = linear_to_srgb(px.r);
+        px.g = linear_to_srgb(px.g);
+        px.b = linear_to_srgb(px.b);
+    }
+}
+
+/// Converts the pixels from the colorspace of `header` to `colorspace` & updates the header. \
+/// Nothing changes, if the image already is in `colorspace`.
+pub fn convert_colorspace(
+    header: &mut Header,
+    pixels: &mut [Pixel],
+    colorspace: ColorSpace,
+) -> Result<(), ColorError> {
+    let amount = header.pixel_amount();
+    let received_size = pixels.len();
+    let pixels = match pixels.get_mut(..amount) {
+        None => Err(MissingPixels {
+            expected_size: amount,
+            received_size,
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+    match (header.colorspace, colorspace) {
+        (ColorSpace::SRGB, ColorSpace::LINEAR) => pixels_to_linear(pixels),
+        (ColorSpace::LINEAR, ColorSpace::SRGB) => pixels_to_srgb(pixels),
+        _ => (),
+    }
+    header.colorspace = colorspace;
+    Ok(())
+}
+
+// c * a / 255, rounded to the nearest integer
+fn mul_div_255(c: u8, a: u8) -> u8 {
+    let t = c as u32 * a as u32 + 128;
+    ((t + (t >> 8)) >> 8) as u8
+}
+
+/// Multiplies the r, g & b channels with alpha, rounding to the nearest value.
+pub fn premultiply(pixels: &mut [Pixel]) {
+    for px in pixels {
+        px.r = mul_div_255(px.r, px.a);
+        px.g = mul_div_255(px.g, px.a);
+        px.b = mul_div_255(px.b, px.a);
+    }
+}
+
+/// Divides the r, g & b channels by alpha, rounding to the nearest value. \
+/// Fully transparent pixels become `Pixel::zero()`, channels larger than alpha are clamped.
+pub fn unpremultiply(pixels: &mut [Pixel]) {
+    for px in pixels {
+        if px.a == 0 {
+            *px = Pixel::zero();
+            continue;
+        }
+        let a = px.a as u32;
+        let div = |c: u8| ((c as u32 * 255 + a / 2) / a).min(255) as u8;
+        px.r = div(px.r);
+        px.g = div(px.g);
+        px.b = div(px.b);
+    }
+}
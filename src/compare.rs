@@ -0,0 +1,169 @@
+use crate::*;
+use std::fmt;
+
+/// Result of comparing two images pixel by pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Amount of pixels which differ in at least one channel.
+    pub mismatches: usize,
+    /// Coordinates (x, y) of the first differing pixel in row-major order.
+    pub first_mismatch: Option<(u32, u32)>,
+    /// Maximum absolute error of the r, g, b & a channels.
+    pub max_error: [u8; 4],
+    /// Peak signal-to-noise ratio in dB, infinite for identical images. \
+    /// Alpha is only included, if one of the images has an alpha channel.
+    pub psnr: f64,
+}
+
+impl Comparison {
+    pub fn is_identical(&self) -> bool {
+        self.mismatches == 0
+    }
+}
+
+pub enum CompareError {
+    /// The images have different dimensions.
+    DimensionMismatch {
+        first: (u32, u32),
+        second: (u32, u32),
+    },
+
+    /// One of the images has fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+}
+
+impl fmt::Debug for CompareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DimensionMismatch { first, second } => {
+                write!(
+                    f,
+                    "Dimension mismatch: {}x{} and {}x{}.",
+                    first.0, first.1, second.0, second.1
+                )
+            }
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+        }
+    }
+}
+
+use CompareError::*;
+
+fn check<'a>(
+    first: (&Header, &'a [Pixel]),
+    second: (&Header, &'a [Pixel]),
+) -> Result<(&'a [Pixel], &'a [Pixel]), CompareError> {
+    let (a, b) = (first.0, second.0);
+    if (a.width, a.height) != (b.width, b.height) {
+        return Err(DimensionMismatch {
+            first: (a.width, a.height),
+            second: (b.width, b.height),
+        });
+    }
+    let amount = a.pixel_amount();
+    let pixels = |pixels: &'a [Pixel]| match pixels.get(..amount) {
+        None => Err(MissingPixels {
+            expected_size: amount,
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    };
+    Ok((pixels(first.1)?, pixels(second.1)?))
+}
+
+/// Compares two images with the same dimensions.
+pub fn compare(
+    first: (&Header, &[Pixel]),
+    second: (&Header, &[Pixel]),
+) -> Result<Comparison, CompareError> {
+    let (a, b) = check(first, second)?;
+    let width = first.0.width as usize;
+    let channels = match (first.0.channels, second.0.channels) {
+        (ColorChannel::RGB, ColorChannel::RGB) => 3,
+        _ => 4,
+    };
+
+    let mut res = Comparison {
+        mismatches: 0,
+        first_mismatch: None,
+        max_error: [0; 4],
+        psnr: f64::INFINITY,
+    };
+    let mut squared_error = 0u64;
+    for (i, (px_a, px_b)) in a.iter().zip(b).enumerate() {
+        if px_a == px_b {
+            continue;
+        }
+        res.mismatches += 1;
+        if res.first_mismatch.is_none() {
+            res.first_mismatch = Some(((i % width) as u32, (i / width) as u32));
+        }
+        let (va, vb) = (
+            [px_a.r, px_a.g, px_a.b, px_a.a],
+            [px_b.r, px_b.g, px_b.b, px_b.a],
+        );
+        for c in 0..4 {
+            let error = va[c].abs_diff(vb[c]);
+            res.max_error[c] = res.max_error[c].max(error);
+            if c < channels {
+                squared_error += error as u64 * error as u64;
+            }
+        }
+    }
+
+    if squared_error > 0 {
+        let mse = squared_error as f64 / (a.len() * channels) as f64;
+        res.psnr = 10.0 * (255.0 * 255.0 / mse).log10();
+    }
+    Ok(res)
+}
+
+/// Creates an image which highlights the differences of two images. \
+/// Differing pixels are red, brighter for larger errors, the others a faded grayscale version of `first`.
+pub fn diff_image(
+    first: (&Header, &[Pixel]),
+    second: (&Header, &[Pixel]),
+) -> Result<(Header, Vec<Pixel>), CompareError> {
+    let (a, b) = check(first, second)?;
+    let pixels = a
+        .iter()
+        .zip(b)
+        .map(|(px_a, px_b)| {
+            let error = [
+                px_a.r.abs_diff(px_b.r),
+                px_a.g.abs_diff(px_b.g),
+                px_a.b.abs_diff(px_b.b),
+                px_a.a.abs_diff(px_b.a),
+            ]
+            .into_iter()
+            .max()
+            .unwrap();
+            if error > 0 {
+                Pixel::from([128 + error / 2, 0, 0])
+            } else {
+                let luma = (px_a.r as u32 * 299 + px_a.g as u32 * 587 + px_a.b as u32 * 114) / 1000;
+                let faded = (160 + luma / 4) as u8;
+                Pixel::from([faded, faded, faded])
+            }
+        })
+        .collect();
+    let header = Header::new(
+        first.0.width,
+        first.0.height,
+        ColorChannel::RGB,
+        ColorSpace::SRGB,
+    );
+    Ok((header, pixels))
+}
@@ -0,0 +1,109 @@
+use crate::{color::*, *};
+
+/// How a source pixel is combined with the destination pixel below it. \
+/// The Porter-Duff operators only use the alpha channels to decide how much of either color remains.
+/// The blend modes mix the colors where both pixels overlap & otherwise composite like `Over`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Operator {
+    /// Source on top of the destination.
+    #[default]
+    Over,
+    /// Source where the destination is opaque, the destination is dropped.
+    In,
+    /// Source where the destination is transparent, the destination is dropped.
+    Out,
+    /// Source on top of the destination, but only where the destination is opaque.
+    Atop,
+    /// Source & destination where the other one is transparent.
+    Xor,
+    /// Darkens, white is neutral.
+    Multiply,
+    /// Lightens, black is neutral.
+    Screen,
+    /// Multiplies dark & screens light destination colors, keeping the contrast of the destination.
+    Overlay,
+}
+
+impl Operator {
+    // Mixes the straight source & destination colors
+    fn blend(&self, cs: f32, cd: f32) -> f32 {
+        let screen = |a: f32, b: f32| a + b - a * b;
+        match self {
+            Operator::Multiply => cs * cd,
+            Operator::Screen => screen(cs, cd),
+            Operator::Overlay => match cd <= 0.5 {
+                true => 2.0 * cs * cd,
+                false => screen(cs, 2.0 * cd - 1.0),
+            },
+            _ => cs,
+        }
+    }
+
+    // Fractions of the source & destination, which remain in the result
+    fn factors(&self, a_src: f32, a_dst: f32) -> (f32, f32) {
+        match self {
+            Operator::In => (a_dst, 0.0),
+            Operator::Out => (1.0 - a_dst, 0.0),
+            Operator::Atop => (a_dst, 1.0 - a_src),
+            Operator::Xor => (1.0 - a_dst, 1.0 - a_src),
+            _ => (1.0, 1.0 - a_src),
+        }
+    }
+}
+
+fn to_f32(px: Pixel, colorspace: ColorSpace) -> [f32; 4] {
+    let channel = |v: u8| match colorspace {
+        ColorSpace::SRGB => srgb_to_linear_table()[v as usize],
+        ColorSpace::LINEAR => v as f32 / 255.0,
+    };
+    [
+        channel(px.r),
+        channel(px.g),
+        channel(px.b),
+        px.a as f32 / 255.0,
+    ]
+}
+
+fn from_f32([r, g, b, a]: [f32; 4], colorspace: ColorSpace) -> Pixel {
+    let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let channel = |v: f32| {
+        to_u8(match colorspace {
+            ColorSpace::SRGB => linear_to_srgb_f32(v.clamp(0.0, 1.0)),
+            ColorSpace::LINEAR => v,
+        })
+    };
+    match to_u8(a) {
+        0 => Pixel::zero(),
+        a => Pixel {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+            a,
+        },
+    }
+}
+
+impl Pixel {
+    /// Composites `self` onto `dst` with `op`. \
+    /// The pixels are interpreted in `colorspace`, so `ColorSpace::SRGB` pixels are blended in linear light.
+    /// To blend the sRGB values directly, like most image editors do, pass `ColorSpace::LINEAR`. \
+    /// A fully transparent result is `Pixel::zero()`.
+    pub fn composite(self, dst: Pixel, op: Operator, colorspace: ColorSpace) -> Pixel {
+        let src = to_f32(self, colorspace);
+        let dst = to_f32(dst, colorspace);
+        let (a_src, a_dst) = (src[3], dst[3]);
+        let (f_src, f_dst) = op.factors(a_src, a_dst);
+        let a = a_src * f_src + a_dst * f_dst;
+        if a <= 0.0 {
+            return Pixel::zero();
+        }
+        let mut res = [0.0; 4];
+        for i in 0..3 {
+            // Where both overlap, the source color is replaced by the blended one
+            let cs = a_src * ((1.0 - a_dst) * src[i] + a_dst * op.blend(src[i], dst[i]));
+            res[i] = (cs * f_src + a_dst * dst[i] * f_dst) / a;
+        }
+        res[3] = a;
+        from_f32(res, colorspace)
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{decode::*, encode::*, *};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+/// Size of the window the encoded input is read through.
+const READ_BUFFER_SIZE: usize = 1 << 16;
+/// Size of the largest chunk, `OP_RGBA`.
+const CHUNK_SIZE_MAX: usize = 5;
+
+pub enum CropError {
+    /// The region is empty or reaches past the image.
+    InvalidRegion {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        image: (u32, u32),
+    },
+
+    Decode(DecodeError),
+
+    Encode(EncodeError),
+}
+
+impl fmt::Debug for CropError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidRegion {
+                x,
+                y,
+                width,
+                height,
+                image,
+            } => {
+                write!(
+                    f,
+                    "Invalid region: {}x{} at ({}, {}) doesn't fit into the {}x{} image.",
+                    width, height, x, y, image.0, image.1
+                )
+            }
+            Self::Decode(err) => write!(f, "{:?}", err),
+            Self::Encode(err) => write!(f, "{:?}", err),
+        }
+    }
+}
+
+impl From<DecodeError> for CropError {
+    fn from(value: DecodeError) -> Self {
+        CropError::Decode(value)
+    }
+}
+
+impl From<EncodeError> for CropError {
+    fn from(value: EncodeError) -> Self {
+        CropError::Encode(value)
+    }
+}
+
+impl From<io::Error> for CropError {
+    fn from(value: io::Error) -> Self {
+        CropError::Decode(DecodeError::IOError(value))
+    }
+}
+
+use CropError::*;
+
+// Reads the encoded input in blocks, so that a whole chunk is always available
+struct ChunkReader<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+    eof: bool,
+}
+
+impl<R: Read> ChunkReader<R> {
+    fn decode_chunk(
+        &mut self,
+        header: &Header,
+        state: &mut DecodeState,
+    ) -> Result<usize, DecodeError> {
+        if self.len - self.pos < CHUNK_SIZE_MAX && !self.eof {
+            self.buf.copy_within(self.pos..self.len, 0);
+            self.len -= self.pos;
+            self.pos = 0;
+            while self.len < CHUNK_SIZE_MAX && !self.eof {
+                match self.reader.read(&mut self.buf[self.len..])? {
+                    0 => self.eof = true,
+                    n => self.len += n,
+                }
+            }
+        }
+        let mut iter = BufIter::new(&self.buf[self.pos..self.len]);
+        let count = decode_chunk(header, &mut iter, state)?;
+        self.pos += iter.idx();
+        Ok(count)
+    }
+}
+
+/// Crops the encoded image read from `reader` to `width x height` pixels starting at (x, y). \
+/// The input is decoded chunk by chunk & only the pixels of the region are kept,
+/// so apart from a fixed read buffer, memory is proportional to the region. \
+/// Decoding stops after the region's last row, anything after it isn't read.
+pub fn crop<R>(reader: R, x: u32, y: u32, width: u32, height: u32) -> Result<Vec<u8>, CropError>
+where
+    R: Read,
+{
+    let mut reader = ChunkReader {
+        reader,
+        buf: vec![0; READ_BUFFER_SIZE],
+        pos: 0,
+        len: 0,
+        eof: false,
+    };
+    let mut head = [0; HEADER_SIZE];
+    match reader.reader.read_exact(&mut head) {
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Err(DecodeError::MissingHeader),
+        res => res.map_err(DecodeError::IOError),
+    }?;
+    let src = decode_header(&head)?;
+
+    let fits = |pos: u32, len: u32, max: u32| {
+        len > 0 && pos.checked_add(len).is_some_and(|end| end <= max)
+    };
+    if !fits(x, width, src.width) || !fits(y, height, src.height) {
+        return Err(InvalidRegion {
+            x,
+            y,
+            width,
+            height,
+            image: (src.width, src.height),
+        });
+    }
+    let header = Header {
+        width,
+        height,
+        ..src
+    };
+    let mut out = vec![0; header.max_size()];
+    let mut out_iter = MutBufIter::new(&mut out);
+    encode_header(&header, &mut out_iter)?;
+
+    let src_width = src.width as usize;
+    let (left, right) = (x as usize, (x + width) as usize);
+    let (top, bottom) = (y as usize, (y + height) as usize);
+    let end = bottom * src_width;
+    let mut row = Vec::with_capacity(width as usize);
+    let mut decode_state = DecodeState::new();
+    let mut encode_state = EncodeState::new();
+    while decode_state.decoded < end {
+        let first = decode_state.decoded;
+        let count = reader.decode_chunk(&src, &mut decode_state)?;
+        for i in first..(first + count).min(end) {
+            let (px_x, px_y) = (i % src_width, i / src_width);
+            if px_y < top || px_x < left || px_x >= right {
+                continue;
+            }
+            row.push(decode_state.prev_pixel);
+            if px_x == right - 1 {
+                let is_last_row = px_y == bottom - 1;
+                encode_row(&header, &row, &mut out_iter, &mut encode_state, is_last_row)?;
+                row.clear();
+            }
+        }
+    }
+    encode_stream_end(&header, &mut out_iter)?;
+    let size = out_iter.idx();
+    out.truncate(size);
+    Ok(out)
+}
+
+/// Crops the image at `input` & writes the result to `output`, see `crop`.
+pub fn crop_file<P, Q>(
+    input: P,
+    output: Q,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<usize, CropError>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let buffer = crop(File::open(input)?, x, y, width, height)?;
+    let mut file = open_file_w(output)?;
+    file.write_all(&buffer)?;
+    Ok(buffer.len())
+}
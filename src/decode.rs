@@ -23,6 +23,8 @@ pub enum DecodeError {
     /// for a header.
     MissingHeader,
 
+    InvalidDimensions(DimensionsError),
+
     /// Issued when the encoding doesn't conform to the spec.
     InvalidEncoding,
 
@@ -41,6 +43,25 @@ pub enum DecodeError {
         received_size: usize,
     },
 
+    /// The last chunk isn't followed by the end marker.
+    /// Only reported by `validate` & `disasm::disassemble`.
+    MissingEndMarker,
+
+    /// The last run produces more pixels than the image contains.
+    /// Only reported by `validate` & `disasm::disassemble`.
+    RunOverflow {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    /// There are bytes after the end marker.
+    /// The amount of trailing bytes is returned.
+    /// Only reported by `validate` & `disasm::disassemble`.
+    TrailingData(usize),
+
+    /// The scan index is malformed or wasn't created for the decoded buffer.
+    InvalidIndex,
+
     IOError(std::io::Error),
 }
 
@@ -70,7 +91,7 @@ impl DecodeError {
 
 impl fmt::Debug for DecodeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &*self {
+        match self {
             Self::InvalidMagic(received_magic) => {
                 write!(
                     f,
@@ -90,6 +111,7 @@ impl fmt::Debug for DecodeError {
                     "Not enough bytes were received to contain the file's Header."
                 )
             }
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
             Self::InvalidEncoding => {
                 write!(f, "The buffer is not properly encoded. Make sure your encoder conforms to the spec found at https://qoiformat.org/qoi-specification.pdf.")
             }
@@ -110,6 +132,32 @@ impl fmt::Debug for DecodeError {
             } => {
                 write!(f, "Pixel Buffer too small: It can fit only {} pixels, but should be able to fit {} pixels.", received_size, expected_size)
             }
+            Self::MissingEndMarker => {
+                write!(
+                    f,
+                    "Missing end marker: The last chunk should be followed by {:?}.",
+                    STREAM_END
+                )
+            }
+            Self::RunOverflow {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Run overflow: The chunks produce {} pixels, but the image only has {} pixels.",
+                    received_size, expected_size
+                )
+            }
+            Self::TrailingData(size) => {
+                write!(f, "Trailing data: {} bytes follow the end marker.", size)
+            }
+            Self::InvalidIndex => {
+                write!(
+                    f,
+                    "The scan index is malformed or doesn't belong to the decoded buffer."
+                )
+            }
             Self::IOError(err) => {
                 write!(f, "IO Error: {}", err)
             }
@@ -123,9 +171,15 @@ impl From<std::io::Error> for DecodeError {
     }
 }
 
+impl From<DimensionsError> for DecodeError {
+    fn from(value: DimensionsError) -> Self {
+        DecodeError::InvalidDimensions(value)
+    }
+}
+
 use DecodeError::*;
 
-fn decode_header(buffer: &[u8]) -> Result<Header, DecodeError> {
+pub fn decode_header(buffer: &[u8]) -> Result<Header, DecodeError> {
     let header_bytes = match buffer.get(..HEADER_SIZE) {
         Some(bytes) => Ok(bytes),
         None => Err(DecodeError::MissingHeader),
@@ -139,163 +193,163 @@ fn decode_header(buffer: &[u8]) -> Result<Header, DecodeError> {
     let header = Header {
         width: u32::from_be_bytes(header_bytes[4..8].try_into().unwrap()),
         height: u32::from_be_bytes(header_bytes[8..12].try_into().unwrap()),
-        channels: header_bytes[12]
-            .try_into()
-            .map_err(|e| InvalidChannels(e))?,
-        colorspace: header_bytes[13]
-            .try_into()
-            .map_err(|e| InvalidColorspace(e))?,
+        channels: header_bytes[12].try_into().map_err(InvalidChannels)?,
+        colorspace: header_bytes[13].try_into().map_err(InvalidColorspace)?,
     };
+    header.check_dimensions()?;
     Ok(header)
 }
 
-fn get_prev_pixel<'a>(pixels: &MutBufIter<'a, Pixel>) -> Pixel {
-    match pixels.look_one_backward() {
-        None => Pixel::def(),
-        Some(pixel) => *pixel,
+pub(crate) struct DecodeState {
+    pub(crate) prev_arr: [Pixel; PREV_ARR_SIZE],
+    pub(crate) prev_pixel: Pixel,
+    /// Amount of pixels decoded so far
+    pub(crate) decoded: usize,
+}
+
+impl DecodeState {
+    pub(crate) fn new() -> Self {
+        DecodeState {
+            prev_arr: [Pixel::zero(); PREV_ARR_SIZE],
+            prev_pixel: Pixel::def(),
+            decoded: 0,
+        }
     }
 }
 
-// Return value indicates whether the decoding process is done
-fn decode_pixels<'a>(
+fn missing_data(header: &Header, state: &DecodeState) -> DecodeError {
+    MissingPixels {
+        expected_size: header.pixel_amount(),
+        received_size: state.decoded,
+    }
+}
+
+// Decodes the next chunk and returns how often the resulting pixel (`state.prev_pixel`) occurs
+pub(crate) fn decode_chunk(
     header: &Header,
-    buffer: &mut BufIter<'a, u8>,
-    pixels: &mut MutBufIter<'a, Pixel>,
-    prev_pixels: &mut [Pixel; PREV_ARR_SIZE],
-) -> Result<(), DecodeError> {
-    loop {
-        // Check if stream is over
-        if let Some(bytes) = buffer.look_forward(STREAM_END_SIZE) {
-            if bytes.starts_with(&STREAM_END) {
-                break;
+    buffer: &mut BufIter<u8>,
+    state: &mut DecodeState,
+) -> Result<usize, DecodeError> {
+    let prev_pixel = state.prev_pixel;
+    let mut count = 1;
+    let pixel = match buffer.step_one() {
+        None => Err(missing_data(header, state)),
+        Some(&byte) => match byte & MASK_2 {
+            OP_INDEX => {
+                // Demasking isn't needed, since OP_INDEX = 0, but for readability & symmetry sake, it's still here
+                let index = (byte & DEMASK_2) as usize;
+                Ok(state.prev_arr[index])
             }
-        }
+            OP_DIFF => {
+                let dr = (byte & 0b00110000) >> 4;
+                let dg = (byte & 0b00001100) >> 2;
+                let db = byte & 0b00000011;
+                Ok(Pixel {
+                    r: prev_pixel.r.wrapping_add(dr).wrapping_sub(DIFF_BIAS),
+                    g: prev_pixel.g.wrapping_add(dg).wrapping_sub(DIFF_BIAS),
+                    b: prev_pixel.b.wrapping_add(db).wrapping_sub(DIFF_BIAS),
+                    a: prev_pixel.a,
+                })
+            }
+            OP_LUMA => {
+                let second_byte = match buffer.step_one() {
+                    None => Err(missing_data(header, state)),
+                    Some(byte) => Ok(byte),
+                }?;
+                let dg = (byte & DEMASK_2) as i8 - LUMA_GREEN_BIAS as i8;
+                let dr = (second_byte & 0b11110000) >> 4;
+                let db = second_byte & 0b00001111;
 
-        // Decode next pixelo
-        let pixel = match buffer.step_one() {
-            None => Err(DecodeError::missing_pixels(header, pixels)),
-            Some(&byte) => match byte & MASK_2 {
-                OP_INDEX => {
-                    // Demasking isn't needed, since OP_INDEX = 0, but for readability & symmetry sake, it's still here
-                    let index = (byte & DEMASK_2) as usize;
-                    let px = prev_pixels[index];
-                    match pixels.set_next_one(prev_pixels[index]) {
-                        None => Err(DecodeError::pixel_buffer_too_small(header, pixels)),
-                        Some(_) => Ok(px),
-                    }
-                }
-                OP_DIFF => {
-                    let prev_pixel = get_prev_pixel(pixels);
-                    let dr = (byte & 0b00110000) >> 4;
-                    let dg = (byte & 0b00001100) >> 2;
-                    let db = (byte & 0b00000011) >> 0;
-                    let px = Pixel {
-                        r: prev_pixel.r.wrapping_add(dr).wrapping_sub(DIFF_BIAS),
-                        g: prev_pixel.g.wrapping_add(dg).wrapping_sub(DIFF_BIAS),
-                        b: prev_pixel.b.wrapping_add(db).wrapping_sub(DIFF_BIAS),
+                let px = if dg < 0 {
+                    Pixel {
+                        r: prev_pixel
+                            .r
+                            .wrapping_sub(-dg as u8)
+                            .wrapping_sub(LUMA_BIAS)
+                            .wrapping_add(dr),
+                        g: prev_pixel.g.wrapping_sub(-dg as u8),
+                        b: prev_pixel
+                            .b
+                            .wrapping_sub(-dg as u8)
+                            .wrapping_sub(LUMA_BIAS)
+                            .wrapping_add(db),
                         a: prev_pixel.a,
-                    };
-                    match pixels.set_next_one(px) {
-                        None => Err(DecodeError::pixel_buffer_too_small(header, pixels)),
-                        Some(_) => Ok(px),
                     }
-                }
-                OP_LUMA => {
-                    let prev_pixel = get_prev_pixel(pixels);
-                    let second_byte = match buffer.step_one() {
-                        None => Err(DecodeError::missing_pixels(header, pixels)),
-                        Some(byte) => Ok(byte),
-                    }?;
-                    let dg = (byte & DEMASK_2) as i8 - LUMA_GREEN_BIAS as i8;
-                    let dr = (second_byte & 0b11110000) >> 4;
-                    let db = (second_byte & 0b00001111) >> 0;
-
-                    let px = if dg < 0 {
-                        Pixel {
-                            r: prev_pixel
-                                .r
-                                .wrapping_sub((-1 * dg) as u8)
-                                .wrapping_sub(LUMA_BIAS)
-                                .wrapping_add(dr),
-                            g: prev_pixel.g.wrapping_sub((-1 * dg) as u8),
-                            b: prev_pixel
-                                .b
-                                .wrapping_sub((-1 * dg) as u8)
-                                .wrapping_sub(LUMA_BIAS)
-                                .wrapping_add(db),
-                            a: prev_pixel.a,
-                        }
-                    } else {
-                        Pixel {
-                            r: prev_pixel
-                                .r
-                                .wrapping_add(dg as u8)
-                                .wrapping_sub(LUMA_BIAS)
-                                .wrapping_add(dr),
-                            g: prev_pixel.g.wrapping_add(dg as u8),
-                            b: prev_pixel
-                                .b
-                                .wrapping_add(dg as u8)
-                                .wrapping_sub(LUMA_BIAS)
-                                .wrapping_add(db),
-                            a: prev_pixel.a,
-                        }
-                    };
-
-                    match pixels.set_next_one(px) {
-                        None => Err(DecodeError::pixel_buffer_too_small(header, pixels)),
-                        Some(_) => Ok(px),
-                    }
-                }
-                MASK_2 => match byte | 1 {
-                    OP_RGB | OP_RGBA => {
-                        let step = if byte == OP_RGB { 3 } else { 4 };
-                        match buffer.step_forward(step) {
-                            None => Err(DecodeError::missing_pixels(header, pixels)),
-                            Some(bytes) => {
-                                let mut px: Pixel = bytes.into();
-                                if byte == OP_RGB {
-                                    px.a = get_prev_pixel(pixels).a;
-                                }
-                                match pixels.set_next_one(px) {
-                                    None => {
-                                        Err(DecodeError::pixel_buffer_too_small(header, pixels))
-                                    }
-                                    Some(_) => Ok(px),
-                                }
-                            }
-                        }
+                } else {
+                    Pixel {
+                        r: prev_pixel
+                            .r
+                            .wrapping_add(dg as u8)
+                            .wrapping_sub(LUMA_BIAS)
+                            .wrapping_add(dr),
+                        g: prev_pixel.g.wrapping_add(dg as u8),
+                        b: prev_pixel
+                            .b
+                            .wrapping_add(dg as u8)
+                            .wrapping_sub(LUMA_BIAS)
+                            .wrapping_add(db),
+                        a: prev_pixel.a,
                     }
-                    _ => {
-                        let run = byte & DEMASK_2;
-                        let &px = match pixels.look_one_backward() {
-                            None => Err(InvalidEncoding),
-                            Some(pixel) => Ok(pixel),
-                        }?;
-
-                        for _ in 0..run + 1 {
-                            if let None = pixels.set_next_one(px.clone()) {
-                                return Err(DecodeError::pixel_buffer_too_small(header, pixels));
+                };
+                Ok(px)
+            }
+            _ => match byte | 1 {
+                OP_RGB | OP_RGBA => {
+                    let step = if byte == OP_RGB { 3 } else { 4 };
+                    match buffer.step_forward(step) {
+                        None => Err(missing_data(header, state)),
+                        Some(bytes) => {
+                            let mut px: Pixel = bytes.into();
+                            if byte == OP_RGB {
+                                px.a = prev_pixel.a;
                             }
+                            Ok(px)
                         }
-
-                        Ok(px)
                     }
-                },
-                _ => unreachable!(),
+                }
+                _ => {
+                    // A leading run repeats the initial previous pixel, see https://github.com/phoboslab/qoi/issues/258
+                    count = (byte & DEMASK_2) as usize + 1;
+                    Ok(prev_pixel)
+                }
             },
-        }?;
+        },
+    }?;
+
+    // Update prev_arr to include the newly added pixel
+    state.prev_arr[pixel.pixel_hash()] = pixel;
+    state.prev_pixel = pixel;
+    state.decoded += count;
+    Ok(count)
+}
+
+pub(crate) fn decode_pixels(
+    header: &Header,
+    buffer: &mut BufIter<u8>,
+    pixels: &mut MutBufIter<Pixel>,
+    state: &mut DecodeState,
+) -> Result<(), DecodeError> {
+    while pixels.idx() < pixels.len() {
+        // Check if stream is over
+        if let Some(bytes) = buffer.look_forward(STREAM_END_SIZE) {
+            if bytes.starts_with(&STREAM_END) {
+                break;
+            }
+        }
 
-        // Update prev_pixels to include the newly added pixel
-        prev_pixels[pixel.pixel_hash() as usize] = pixel;
+        let count = decode_chunk(header, buffer, state)?;
+        for _ in 0..count {
+            if pixels.set_next_one(state.prev_pixel).is_none() {
+                return Err(DecodeError::pixel_buffer_too_small(header, pixels));
+            }
+        }
     }
     Ok(())
 }
 
-pub fn decode<'a>(buffer: &[u8], pixels: &mut [Pixel]) -> Result<Header, DecodeError> {
+pub fn decode(buffer: &[u8], pixels: &mut [Pixel]) -> Result<Header, DecodeError> {
     let header = decode_header(buffer)?;
     let pixel_amount = header.pixel_amount();
-    let mut prev_pixels = [Pixel::zero(); PREV_ARR_SIZE];
 
     let mut pixels = match MutBufIter::from(pixels, ..pixel_amount) {
         None => Err(DecodeError::PixelBufferTooSmall {
@@ -304,16 +358,15 @@ pub fn decode<'a>(buffer: &[u8], pixels: &mut [Pixel]) -> Result<Header, DecodeE
         }),
         Some(pixels) => Ok(pixels),
     }?;
-    let mut buffer = BufIter::from(buffer, HEADER_SIZE.into()..).unwrap();
+    let mut buffer = BufIter::from(buffer, HEADER_SIZE..).unwrap();
 
-    decode_pixels(&header, &mut buffer, &mut pixels, &mut prev_pixels)?;
+    decode_pixels(&header, &mut buffer, &mut pixels, &mut DecodeState::new())?;
     Ok(header)
 }
 
-pub fn decode_allocated<'a>(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), DecodeError> {
+pub fn decode_allocated(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), DecodeError> {
     let header = decode_header(buffer)?;
     let pixel_amount = header.pixel_amount();
-    let mut prev_pixels = [Pixel::zero(); PREV_ARR_SIZE];
 
     let mut pixels_vec = vec![Pixel::def(); pixel_amount];
     let mut pixels = match MutBufIter::from(&mut pixels_vec, ..pixel_amount) {
@@ -323,8 +376,45 @@ pub fn decode_allocated<'a>(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), Decod
         }),
         Some(pixels) => Ok(pixels),
     }?;
-    let mut buffer = BufIter::from(buffer, HEADER_SIZE.into()..).unwrap();
+    let mut buffer = BufIter::from(buffer, HEADER_SIZE..).unwrap();
 
-    decode_pixels(&header, &mut buffer, &mut pixels, &mut prev_pixels)?;
+    decode_pixels(&header, &mut buffer, &mut pixels, &mut DecodeState::new())?;
     Ok((header, pixels_vec))
 }
+
+/// Checks that `buffer` strictly conforms to the spec:
+/// The chunks produce exactly `width * height` pixels and are followed by the end marker,
+/// which is the last thing in the buffer.
+pub fn validate(buffer: &[u8]) -> Result<Header, DecodeError> {
+    let header = decode_header(buffer)?;
+    let pixel_amount = header.pixel_amount();
+    let mut buffer = BufIter::from(buffer, HEADER_SIZE..).unwrap();
+    let mut state = DecodeState::new();
+
+    while state.decoded < pixel_amount {
+        decode_chunk(&header, &mut buffer, &mut state)?;
+    }
+    check_end(&header, &mut buffer, &state)?;
+    Ok(header)
+}
+
+// Checks what follows the last chunk, once all pixels are decoded
+pub(crate) fn check_end(
+    header: &Header,
+    buffer: &mut BufIter<u8>,
+    state: &DecodeState,
+) -> Result<(), DecodeError> {
+    if state.decoded > header.pixel_amount() {
+        return Err(RunOverflow {
+            expected_size: header.pixel_amount(),
+            received_size: state.decoded,
+        });
+    }
+    if buffer.step_forward(STREAM_END_SIZE) != Some(&STREAM_END) {
+        return Err(MissingEndMarker);
+    }
+    match buffer.len() - buffer.idx() {
+        0 => Ok(()),
+        trailing => Err(TrailingData(trailing)),
+    }
+}
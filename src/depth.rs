@@ -0,0 +1,152 @@
+use crate::{
+    encode::{encode_allocated, EncodeError},
+    *,
+};
+use std::fmt;
+
+/// 8x8 Bayer matrix, the thresholds of `Dither::Ordered`.
+const BAYER: [[u32; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// How 16-bit samples are reduced to 8 bits. \
+/// All of them keep samples, which were expanded from 8 bits (`v * 257`), as they were.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dither {
+    /// Rounds to the nearest 8-bit value.
+    #[default]
+    Round,
+    /// Drops the low byte.
+    Truncate,
+    /// Rounds against the thresholds of an 8x8 Bayer matrix, a fixed pattern that compresses well.
+    Ordered,
+    /// Floyd-Steinberg error diffusion, passes the rounding error on to the neighbouring pixels.
+    FloydSteinberg,
+}
+
+pub enum DepthError {
+    InvalidDimensions(DimensionsError),
+
+    /// The amount of samples doesn't match `width * height * channels`.
+    SizeMismatch {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    Encode(EncodeError),
+}
+
+impl fmt::Debug for DepthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::SizeMismatch {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Size mismatch: Expected {} samples, instead received {} samples.",
+                    expected_size, received_size
+                )
+            }
+            Self::Encode(err) => write!(f, "{:?}", err),
+        }
+    }
+}
+
+impl From<EncodeError> for DepthError {
+    fn from(value: EncodeError) -> Self {
+        DepthError::Encode(value)
+    }
+}
+
+impl From<DimensionsError> for DepthError {
+    fn from(value: DimensionsError) -> Self {
+        DepthError::InvalidDimensions(value)
+    }
+}
+
+use DepthError::*;
+
+// Error diffusion over the whole image, the errors are kept in 16-bit units
+fn floyd_steinberg(samples: &[u16], width: usize, channels: usize) -> Vec<u8> {
+    let row_len = width * channels;
+    let mut out = Vec::with_capacity(samples.len());
+    // Errors of the current & the next row, with a spare pixel at both ends
+    let mut errors = vec![0i32; row_len + 2 * channels];
+    let mut next = vec![0i32; row_len + 2 * channels];
+    for row in samples.chunks_exact(row_len) {
+        for (i, &v) in row.iter().enumerate() {
+            let value = (v as i32 + errors[i + channels] / 16).clamp(0, u16::MAX as i32);
+            let quantized = u16_to_u8(value as u16);
+            let error = value - quantized as i32 * 257;
+            out.push(quantized);
+            errors[i + 2 * channels] += error * 7;
+            next[i] += error * 3;
+            next[i + channels] += error * 5;
+            next[i + 2 * channels] += error;
+        }
+        std::mem::swap(&mut errors, &mut next);
+        next.fill(0);
+    }
+    out
+}
+
+/// Reduces interleaved 16-bit samples with the channels of `header` to pixels. \
+/// Alpha is reduced the same way as the colors.
+pub fn downconvert(
+    header: &Header,
+    samples: &[u16],
+    dither: Dither,
+) -> Result<Vec<Pixel>, DepthError> {
+    header.check_dimensions()?;
+    let channels = header.bytes_per_pixel();
+    let expected_size = header.pixel_amount() * channels;
+    if samples.len() != expected_size {
+        return Err(SizeMismatch {
+            expected_size,
+            received_size: samples.len(),
+        });
+    }
+
+    let width = header.width as usize;
+    let bytes: Vec<u8> = match dither {
+        Dither::Round => samples.iter().map(|&v| u16_to_u8(v)).collect(),
+        Dither::Truncate => samples.iter().map(|&v| (v >> 8) as u8).collect(),
+        Dither::Ordered => samples
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let (x, y) = (i / channels % width, i / channels / width);
+                // Rounds v / 257 + (t + 0.5) / 64 down
+                let t = 2 * BAYER[y % 8][x % 8] + 1;
+                ((v as u32 * 128 + t * 257) / (257 * 128)).min(255) as u8
+            })
+            .collect(),
+        Dither::FloydSteinberg => floyd_steinberg(samples, width, channels),
+    };
+    let pixels = bytes
+        .chunks_exact(channels)
+        .map(|bytes| Pixel {
+            r: bytes[0],
+            g: bytes[1],
+            b: bytes[2],
+            a: bytes.get(3).copied().unwrap_or(255),
+        })
+        .collect();
+    Ok(pixels)
+}
+
+/// Reduces the 16-bit samples with `dither` & encodes the result, see `downconvert`.
+pub fn encode(header: &Header, samples: &[u16], dither: Dither) -> Result<Vec<u8>, DepthError> {
+    let pixels = downconvert(header, samples, dither)?;
+    Ok(encode_allocated(header, &pixels)?)
+}
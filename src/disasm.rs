@@ -0,0 +1,161 @@
+use crate::{decode::*, *};
+use std::fmt;
+
+/// A single chunk with its decoded arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chunk {
+    RGB([u8; 3]),
+    RGBA([u8; 4]),
+    INDEX(u8),
+    /// Differences to the previous pixel, each between -2 & 1.
+    DIFF {
+        dr: i8,
+        dg: i8,
+        db: i8,
+    },
+    /// `dg` is between -32 & 31, `dr_dg` & `db_dg` are the red & blue differences \
+    /// minus `dg`, each between -8 & 7.
+    LUMA {
+        dg: i8,
+        dr_dg: i8,
+        db_dg: i8,
+    },
+    /// Length of the run between 1 & 62.
+    RUN(u8),
+}
+
+impl Chunk {
+    /// Parses a complete chunk, `None` if `bytes` is too short for its opcode.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let &byte = bytes.first()?;
+        let opcode = Opcode::from_byte(byte);
+        let bytes = bytes.get(..opcode.size())?;
+        let chunk = match opcode {
+            Opcode::RGB => Chunk::RGB([bytes[1], bytes[2], bytes[3]]),
+            Opcode::RGBA => Chunk::RGBA([bytes[1], bytes[2], bytes[3], bytes[4]]),
+            Opcode::INDEX => Chunk::INDEX(byte & DEMASK_2),
+            Opcode::DIFF => Chunk::DIFF {
+                dr: ((byte >> 4) & 0b11) as i8 - DIFF_BIAS as i8,
+                dg: ((byte >> 2) & 0b11) as i8 - DIFF_BIAS as i8,
+                db: (byte & 0b11) as i8 - DIFF_BIAS as i8,
+            },
+            Opcode::LUMA => Chunk::LUMA {
+                dg: (byte & DEMASK_2) as i8 - LUMA_GREEN_BIAS as i8,
+                dr_dg: (bytes[1] >> 4) as i8 - LUMA_BIAS as i8,
+                db_dg: (bytes[1] & 0b1111) as i8 - LUMA_BIAS as i8,
+            },
+            Opcode::RUN => Chunk::RUN((byte & DEMASK_2) + 1),
+        };
+        Some(chunk)
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            Chunk::RGB(_) => Opcode::RGB,
+            Chunk::RGBA(_) => Opcode::RGBA,
+            Chunk::INDEX(_) => Opcode::INDEX,
+            Chunk::DIFF { .. } => Opcode::DIFF,
+            Chunk::LUMA { .. } => Opcode::LUMA,
+            Chunk::RUN(_) => Opcode::RUN,
+        }
+    }
+}
+
+/// Writes the opcode name followed by the arguments, e.g. `QOI_OP_DIFF dr -1 dg 0 db 1`.
+impl fmt::Display for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.opcode().name();
+        match self {
+            Chunk::RGB([r, g, b]) => write!(f, "{:<12} r {} g {} b {}", name, r, g, b),
+            Chunk::RGBA([r, g, b, a]) => {
+                write!(f, "{:<12} r {} g {} b {} a {}", name, r, g, b, a)
+            }
+            Chunk::INDEX(index) => write!(f, "{:<12} index {}", name, index),
+            Chunk::DIFF { dr, dg, db } => {
+                write!(f, "{:<12} dr {} dg {} db {}", name, dr, dg, db)
+            }
+            Chunk::LUMA { dg, dr_dg, db_dg } => {
+                write!(f, "{:<12} dg {} dr-dg {} db-dg {}", name, dg, dr_dg, db_dg)
+            }
+            Chunk::RUN(length) => write!(f, "{:<12} length {}", name, length),
+        }
+    }
+}
+
+/// A chunk & where it's located in the encoded buffer & the image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// Offset of the first byte in the encoded buffer.
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub chunk: Chunk,
+    /// Coordinates (x, y) of the first & last pixel the chunk produces.
+    pub first: (u32, u32),
+    pub last: (u32, u32),
+}
+
+/// Writes the offset, raw bytes, chunk & pixel coordinates.
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let chunk = self.chunk.to_string();
+        write!(
+            f,
+            "{:08x}  {:<14}  {:<40} ({}, {})",
+            self.offset,
+            bytes.join(" "),
+            chunk,
+            self.first.0,
+            self.first.1
+        )?;
+        if self.first != self.last {
+            write!(f, " .. ({}, {})", self.last.0, self.last.1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of `disassemble`, `error` is the first problem found after the header.
+pub struct Disassembly {
+    pub header: Header,
+    pub lines: Vec<Line>,
+    /// Offset after the last complete chunk.
+    pub end: usize,
+    pub error: Option<DecodeError>,
+}
+
+/// Walks over the chunks of an encoded image, until all pixels are decoded or an error occurs. \
+/// Afterwards the end marker is checked as strictly as `validate` does.
+pub fn disassemble(buffer: &[u8]) -> Result<Disassembly, DecodeError> {
+    let header = decode_header(buffer)?;
+    let pixel_amount = header.pixel_amount();
+    let width = header.width as usize;
+    let coords = |i: usize| ((i % width) as u32, (i / width) as u32);
+    let mut iter = BufIter::from(buffer, HEADER_SIZE..).unwrap();
+    let mut state = DecodeState::new();
+
+    let mut res = Disassembly {
+        header,
+        lines: Vec::new(),
+        end: HEADER_SIZE,
+        error: None,
+    };
+    while state.decoded < pixel_amount {
+        let (offset, first) = (HEADER_SIZE + iter.idx(), state.decoded);
+        if let Err(err) = decode_chunk(&header, &mut iter, &mut state) {
+            res.error = Some(err);
+            return Ok(res);
+        }
+        let bytes = &buffer[offset..HEADER_SIZE + iter.idx()];
+        res.lines.push(Line {
+            offset,
+            bytes: bytes.to_vec(),
+            chunk: Chunk::from_bytes(bytes).unwrap(),
+            first: coords(first),
+            last: coords(state.decoded - 1),
+        });
+        res.end = HEADER_SIZE + iter.idx();
+    }
+    res.error = check_end(&header, &mut iter, &state).err();
+    Ok(res)
+}
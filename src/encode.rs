@@ -1,4 +1,4 @@
-use crate::*;
+use crate::{image::ImageView, *};
 use std::fmt;
 
 pub enum EncodeError {
@@ -17,6 +17,8 @@ pub enum EncodeError {
         received_size: usize,
     },
 
+    InvalidDimensions(DimensionsError),
+
     IOError(std::io::Error),
 }
 
@@ -35,7 +37,7 @@ impl EncodeError {
 
 impl fmt::Debug for EncodeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match &*self {
+        match self {
             Self::MissingPixels {
                 expected_size,
                 received_size,
@@ -53,6 +55,7 @@ impl fmt::Debug for EncodeError {
             } => {
                 write!(f, "Output Buffer too small: Only {} bytes were received. Try again with a size of at least {} bytes", received_size, expected_size)
             }
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
             Self::IOError(err) => {
                 write!(f, "IO Error: {}", err)
             }
@@ -84,12 +87,19 @@ impl From<EncodeAttemptRes> for Result<(), EncodeError> {
 }
 
 use EncodeAttemptRes::*;
+impl From<DimensionsError> for EncodeError {
+    fn from(value: DimensionsError) -> Self {
+        EncodeError::InvalidDimensions(value)
+    }
+}
+
 use EncodeError::*;
 
-fn encode_header<'a>(
+pub(crate) fn encode_header(
     header: &Header,
-    buffer: &mut MutBufIter<'a, u8>,
+    buffer: &mut MutBufIter<u8>,
 ) -> Result<usize, EncodeError> {
+    header.check_dimensions()?;
     if buffer.len() < HEADER_SIZE {
         return Err(EncodeError::buffer_too_small(header, buffer));
     }
@@ -162,7 +172,7 @@ fn try_op_diff_luma(
         let db = pixel.b.wrapping_sub(prev_pixel.b).wrapping_add(DIFF_BIAS);
 
         if dr <= 3 && dg <= 3 && db <= 3 {
-            return match buffer.set_next_one(OP_DIFF | (dr << 4) | (dg << 2) | (db << 0)) {
+            return match buffer.set_next_one(OP_DIFF | (dr << 4) | (dg << 2) | db) {
                 None => Failure(EncodeError::buffer_too_small(header, buffer)),
                 Some(_) => Success,
             };
@@ -184,7 +194,7 @@ fn try_op_diff_luma(
         let dg = dg.wrapping_add(LUMA_GREEN_BIAS);
 
         if dg <= 63 && dr <= 15 && db <= 15 {
-            return match buffer.set_next(&[OP_LUMA | dg, (dr << 4) | (db << 0)]) {
+            return match buffer.set_next(&[OP_LUMA | dg, (dr << 4) | db]) {
                 None => Failure(EncodeError::buffer_too_small(header, buffer)),
                 Some(_) => Success,
             };
@@ -209,27 +219,81 @@ fn try_op_pixel(
             }
         }
     } else {
-        match buffer.step_forward_mut(5) {
-            None => Failure(EncodeError::buffer_too_small(header, buffer)),
-            Some(bytes) => {
-                bytes[0] = OP_RGBA;
-                bytes[1..5].copy_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
-                Success
-            }
+        try_op_rgba(header, pixel, buffer)
+    }
+}
+
+fn try_op_rgba(header: &Header, pixel: Pixel, buffer: &mut MutBufIter<u8>) -> EncodeAttemptRes {
+    match buffer.step_forward_mut(5) {
+        None => Failure(EncodeError::buffer_too_small(header, buffer)),
+        Some(bytes) => {
+            bytes[0] = OP_RGBA;
+            bytes[1..5].copy_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+            Success
+        }
+    }
+}
+
+/// Options for `encode_with` & `encode_allocated_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EncodeOptions {
+    /// Encodes fully transparent pixels as `Pixel::zero()`, since their color is invisible anyway. \
+    /// This turns transparent areas with varying colors into runs & index hits.
+    pub canonical_transparent: bool,
+}
+
+pub(crate) struct EncodeState {
+    prev_arr: [Pixel; PREV_ARR_SIZE],
+    prev_pixel: Pixel,
+    run: u8,
+    is_first: bool,
+    detached: bool,
+    canonical_transparent: bool,
+}
+
+impl EncodeState {
+    pub(crate) fn new() -> Self {
+        EncodeState {
+            prev_arr: [Pixel::zero(); PREV_ARR_SIZE],
+            prev_pixel: Pixel::def(),
+            run: 0,
+            is_first: true,
+            detached: false,
+            canonical_transparent: false,
         }
     }
+
+    pub(crate) fn with_options(options: EncodeOptions) -> Self {
+        EncodeState {
+            canonical_transparent: options.canonical_transparent,
+            ..EncodeState::new()
+        }
+    }
+
+    /// State for a stream that continues another stream, but that should also be decodable on its own,
+    /// i.e. with a freshly reset decoder state.
+    /// The first pixel is always written as `OP_RGBA` and `OP_INDEX` only refers to pixels of this stream.
+    pub(crate) fn detached() -> Self {
+        let mut state = EncodeState::new();
+        // Pixel::zero() hashes to 0, so slot 0 would produce a false hit that
+        // a decoder continuing the previous stream wouldn't reproduce.
+        // Any pixel that doesn't hash to 0 can never match here.
+        state.prev_arr[0] = Pixel {
+            a: 1,
+            ..Pixel::zero()
+        };
+        state.detached = true;
+        state
+    }
 }
 
-fn encode_pixel<'a>(
+fn encode_pixel(
     header: &Header,
-    pixels: &mut BufIter<'a, Pixel>,
-    buffer: &mut MutBufIter<'a, u8>,
-    prev_arr: &mut [Pixel; PREV_ARR_SIZE],
-    prev_pixel: &Pixel,
-    run: &mut u8,
+    pixels: &mut BufIter<Pixel>,
+    buffer: &mut MutBufIter<u8>,
+    state: &mut EncodeState,
     is_last: bool,
-    is_first: bool,
-) -> Result<Pixel, EncodeError> {
+) -> Result<(), EncodeError> {
     let &pixel = match pixels.step_one() {
         None => Err(MissingPixels {
             expected_size: header.pixel_amount(),
@@ -237,37 +301,111 @@ fn encode_pixel<'a>(
         }),
         Some(pixel) => Ok(pixel),
     }?;
+    let pixel = match state.canonical_transparent && pixel.a == 0 {
+        true => Pixel::zero(),
+        false => pixel,
+    };
 
-    let index = pixel.pixel_hash() as usize;
-    let res = Ok(pixel);
-    let res = match try_op_run(header, pixel, buffer, prev_pixel, run, is_last, is_first) {
-        Success => res,
-        Failure(e) => Err(e),
-        Invalid => match try_op_index(header, pixel, index, buffer, prev_arr) {
-            Success => res,
-            Failure(e) => Err(e),
-            Invalid => match try_op_diff_luma(header, pixel, buffer, prev_pixel) {
-                Success => res,
-                Failure(e) => Err(e),
-                Invalid => match try_op_pixel(header, pixel, prev_pixel, buffer) {
-                    Success => res,
-                    Failure(e) => Err(e),
-                    Invalid => unreachable!(),
+    let index = pixel.pixel_hash();
+    let prev_pixel = &state.prev_pixel;
+    let res = if state.is_first && state.detached {
+        try_op_rgba(header, pixel, buffer)
+    } else {
+        match try_op_run(
+            header,
+            pixel,
+            buffer,
+            prev_pixel,
+            &mut state.run,
+            is_last,
+            state.is_first,
+        ) {
+            Invalid => match try_op_index(header, pixel, index, buffer, &mut state.prev_arr) {
+                Invalid => match try_op_diff_luma(header, pixel, buffer, prev_pixel) {
+                    Invalid => try_op_pixel(header, pixel, prev_pixel, buffer),
+                    res => res,
                 },
+                res => res,
             },
-        },
+            res => res,
+        }
     };
-    prev_arr[index] = pixel;
-    res
+    state.prev_arr[index] = pixel;
+    state.prev_pixel = pixel;
+    state.is_first = false;
+    match res {
+        Success => Ok(()),
+        Failure(e) => Err(e),
+        Invalid => unreachable!(),
+    }
 }
 
-pub fn encode<'a>(
+/// Encodes all `pixels` as chunks into `buffer`, without header or end marker.
+pub(crate) fn encode_chunks(
+    header: &Header,
+    pixels: &[Pixel],
+    buffer: &mut MutBufIter<u8>,
+    state: &mut EncodeState,
+) -> Result<(), EncodeError> {
+    encode_rows(header, std::iter::once(pixels), buffer, state)
+}
+
+/// Like `encode_chunks`, but the pixels are split into rows. \
+/// Runs continue across rows, the result is the same as for the concatenated rows.
+pub(crate) fn encode_rows<'a, I>(
+    header: &Header,
+    rows: I,
+    buffer: &mut MutBufIter<u8>,
+    state: &mut EncodeState,
+) -> Result<(), EncodeError>
+where
+    I: Iterator<Item = &'a [Pixel]>,
+{
+    let mut rows = rows.peekable();
+    while let Some(row) = rows.next() {
+        encode_row(header, row, buffer, state, rows.peek().is_none())?;
+    }
+    Ok(())
+}
+
+/// Encodes a single row of a stream, a run is only finished at the end of the last row.
+pub(crate) fn encode_row(
+    header: &Header,
+    row: &[Pixel],
+    buffer: &mut MutBufIter<u8>,
+    state: &mut EncodeState,
+    is_last_row: bool,
+) -> Result<(), EncodeError> {
+    let mut pixels = BufIter::new(row);
+    while pixels.idx() < pixels.len() {
+        let is_last = is_last_row && pixels.idx() == pixels.len() - 1;
+        encode_pixel(header, &mut pixels, buffer, state, is_last)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn encode_stream_end(
+    header: &Header,
+    buffer: &mut MutBufIter<u8>,
+) -> Result<usize, EncodeError> {
+    match buffer.set_next(&STREAM_END) {
+        None => Err(EncodeError::buffer_too_small(header, buffer)),
+        Some(size) => Ok(size),
+    }
+}
+
+pub fn encode(header: &Header, pixels: &[Pixel], buffer: &mut [u8]) -> Result<usize, EncodeError> {
+    encode_with(header, pixels, buffer, EncodeOptions::default())
+}
+
+pub fn encode_with(
     header: &Header,
     pixels: &[Pixel],
     buffer: &mut [u8],
+    options: EncodeOptions,
 ) -> Result<usize, EncodeError> {
     let pixel_amount = header.pixel_amount();
-    let mut pixels = match BufIter::from(pixels, ..pixel_amount) {
+    let pixels = match pixels.get(..pixel_amount) {
         None => Err(MissingPixels {
             expected_size: pixel_amount,
             received_size: pixels.len(),
@@ -276,35 +414,42 @@ pub fn encode<'a>(
     }?;
     let mut buffer = MutBufIter::new(buffer);
 
-    let mut prev_arr = [Pixel::zero(); PREV_ARR_SIZE];
-    let mut prev_pixel = Pixel::def();
-    let mut run = 0;
-    let mut is_first = true;
-
     encode_header(header, &mut buffer)?;
-    while pixels.idx() < pixel_amount {
-        let is_last = pixels.idx() == pixel_amount - 1;
-        prev_pixel = encode_pixel(
-            header,
-            &mut pixels,
-            &mut buffer,
-            &mut prev_arr,
-            &prev_pixel,
-            &mut run,
-            is_last,
-            is_first,
-        )?;
-        is_first = false;
-    }
-
-    buffer.copy_from_slice(&STREAM_END, STREAM_END_SIZE, true);
+    let mut state = EncodeState::with_options(options);
+    encode_chunks(header, pixels, &mut buffer, &mut state)?;
+    encode_stream_end(header, &mut buffer)?;
 
     Ok(buffer.idx())
 }
 
 pub fn encode_allocated(header: &Header, pixels: &[Pixel]) -> Result<Vec<u8>, EncodeError> {
+    encode_allocated_with(header, pixels, EncodeOptions::default())
+}
+
+pub fn encode_allocated_with(
+    header: &Header,
+    pixels: &[Pixel],
+    options: EncodeOptions,
+) -> Result<Vec<u8>, EncodeError> {
     let mut buffer = vec![0; header.max_size()];
-    let out_size = encode(header, pixels, &mut buffer)?;
+    let out_size = encode_with(header, pixels, &mut buffer, options)?;
     buffer.truncate(out_size);
     Ok(buffer)
 }
+
+/// Encodes the pixels of `view`, which don't need to be contiguous in memory.
+pub fn encode_view(
+    view: &ImageView,
+    channels: ColorChannel,
+    colorspace: ColorSpace,
+    buffer: &mut [u8],
+) -> Result<usize, EncodeError> {
+    let header = Header::new(view.width(), view.height(), channels, colorspace);
+    let mut buffer = MutBufIter::new(buffer);
+
+    encode_header(&header, &mut buffer)?;
+    encode_rows(&header, view.rows(), &mut buffer, &mut EncodeState::new())?;
+    encode_stream_end(&header, &mut buffer)?;
+
+    Ok(buffer.idx())
+}
@@ -0,0 +1,152 @@
+use crate::*;
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+pub const FARBFELD_MAGIC: [u8; 8] = *b"farbfeld";
+pub const FARBFELD_HEADER_SIZE: usize = 16;
+
+pub enum FarbfeldError {
+    /// The data doesn't start with "farbfeld".
+    InvalidMagic,
+
+    /// The data is shorter than the header.
+    MissingHeader,
+
+    InvalidDimensions(DimensionsError),
+
+    /// The data is too short for the image's dimensions. \
+    /// When encoding, there are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    IOError(std::io::Error),
+}
+
+impl fmt::Debug for FarbfeldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => {
+                write!(
+                    f,
+                    "Invalid magic: The data doesn't start with {:?}.",
+                    FARBFELD_MAGIC
+                )
+            }
+            Self::MissingHeader => {
+                write!(
+                    f,
+                    "Missing Header: The data is shorter than {} bytes.",
+                    FARBFELD_HEADER_SIZE
+                )
+            }
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} bytes, instead received {} bytes.",
+                    expected_size, received_size
+                )
+            }
+            Self::IOError(err) => write!(f, "IO Error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for FarbfeldError {
+    fn from(value: std::io::Error) -> Self {
+        FarbfeldError::IOError(value)
+    }
+}
+
+impl From<DimensionsError> for FarbfeldError {
+    fn from(value: DimensionsError) -> Self {
+        FarbfeldError::InvalidDimensions(value)
+    }
+}
+
+use FarbfeldError::*;
+
+/// Decodes a farbfeld image into RGBA pixels. \
+/// The 16-bit samples are rounded to the nearest 8-bit value.
+pub fn decode(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), FarbfeldError> {
+    let head = buffer.get(..FARBFELD_HEADER_SIZE).ok_or(MissingHeader)?;
+    if head[..8] != FARBFELD_MAGIC {
+        return Err(InvalidMagic);
+    }
+    let width = u32::from_be_bytes(head[8..12].try_into().unwrap());
+    let height = u32::from_be_bytes(head[12..16].try_into().unwrap());
+    let header = Header::new(width, height, ColorChannel::RGBA, ColorSpace::SRGB);
+    header.check_dimensions()?;
+
+    let expected_size = header.pixel_amount() * 8;
+    let data = &buffer[FARBFELD_HEADER_SIZE..];
+    let data = match data.get(..expected_size) {
+        None => Err(MissingPixels {
+            expected_size,
+            received_size: data.len(),
+        }),
+        Some(data) => Ok(data),
+    }?;
+
+    let sample =
+        |bytes: &[u8], i: usize| u16_to_u8(u16::from_be_bytes([bytes[2 * i], bytes[2 * i + 1]]));
+    let pixels = data
+        .chunks(8)
+        .map(|bytes| Pixel {
+            r: sample(bytes, 0),
+            g: sample(bytes, 1),
+            b: sample(bytes, 2),
+            a: sample(bytes, 3),
+        })
+        .collect();
+    Ok((header, pixels))
+}
+
+/// Encodes a farbfeld image, the 8-bit samples are expanded to the full 16-bit range.
+pub fn encode(header: &Header, pixels: &[Pixel]) -> Result<Vec<u8>, FarbfeldError> {
+    header.check_dimensions()?;
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+
+    let mut out = Vec::with_capacity(FARBFELD_HEADER_SIZE + pixels.len() * 8);
+    out.extend_from_slice(&FARBFELD_MAGIC);
+    out.extend_from_slice(&header.width.to_be_bytes());
+    out.extend_from_slice(&header.height.to_be_bytes());
+    for px in pixels {
+        for val in [px.r, px.g, px.b, px.a] {
+            out.extend_from_slice(&(val as u16 * 257).to_be_bytes());
+        }
+    }
+    Ok(out)
+}
+
+pub fn read<P>(filepath: P) -> Result<(Header, Vec<Pixel>), FarbfeldError>
+where
+    P: AsRef<Path>,
+{
+    decode(&fs::read(filepath)?)
+}
+
+pub fn write<P>(filepath: P, header: &Header, pixels: &[Pixel]) -> Result<usize, FarbfeldError>
+where
+    P: AsRef<Path>,
+{
+    let buffer = encode(header, pixels)?;
+    let mut file: File = open_file_w(filepath)?;
+    file.write_all(&buffer)?;
+    Ok(buffer.len())
+}
@@ -0,0 +1,192 @@
+use crate::{
+    decode::{decode_allocated, DecodeError},
+    encode::{encode_allocated, EncodeError},
+    *,
+};
+use std::fmt;
+
+/// Channels of a grayscale buffer, luminance with or without alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrayLayout {
+    L,
+    LA,
+}
+
+impl GrayLayout {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            GrayLayout::L => 1,
+            GrayLayout::LA => 2,
+        }
+    }
+
+    /// Channels of the encoded image, gray is stored as RGB(A).
+    pub fn channels(&self) -> ColorChannel {
+        match self {
+            GrayLayout::L => ColorChannel::RGB,
+            GrayLayout::LA => ColorChannel::RGBA,
+        }
+    }
+}
+
+pub enum GrayError {
+    InvalidDimensions(DimensionsError),
+
+    /// The size of the buffer doesn't match the dimensions & layout.
+    SizeMismatch {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    /// When collapsing, there are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    /// The pixel at (x, y) has differing r, g & b channels.
+    NotGray {
+        x: u32,
+        y: u32,
+    },
+
+    Decode(DecodeError),
+
+    Encode(EncodeError),
+}
+
+impl fmt::Debug for GrayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::SizeMismatch {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Size mismatch: Expected {} bytes, instead received {} bytes.",
+                    expected_size, received_size
+                )
+            }
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+            Self::NotGray { x, y } => {
+                write!(f, "Not gray: The pixel at ({}, {}) has color.", x, y)
+            }
+            Self::Decode(err) => write!(f, "{:?}", err),
+            Self::Encode(err) => write!(f, "{:?}", err),
+        }
+    }
+}
+
+impl From<DecodeError> for GrayError {
+    fn from(value: DecodeError) -> Self {
+        GrayError::Decode(value)
+    }
+}
+
+impl From<EncodeError> for GrayError {
+    fn from(value: EncodeError) -> Self {
+        GrayError::Encode(value)
+    }
+}
+
+impl From<DimensionsError> for GrayError {
+    fn from(value: DimensionsError) -> Self {
+        GrayError::InvalidDimensions(value)
+    }
+}
+
+use GrayError::*;
+
+/// Expands grayscale values to pixels, pixels of `GrayLayout::L` are opaque.
+pub fn expand(buffer: &[u8], layout: GrayLayout) -> Vec<Pixel> {
+    buffer
+        .chunks_exact(layout.bytes_per_pixel())
+        .map(|bytes| {
+            let (l, a) = (bytes[0], bytes.get(1).copied().unwrap_or(255));
+            Pixel::from([l, l, l, a])
+        })
+        .collect()
+}
+
+// Index of the first pixel with color, if there is one
+fn find_color(pixels: &[Pixel]) -> Option<usize> {
+    pixels.iter().position(|px| px.r != px.g || px.g != px.b)
+}
+
+/// Layout the pixels can be collapsed to without loss, `None` if any pixel has color.
+pub fn gray_layout(pixels: &[Pixel]) -> Option<GrayLayout> {
+    match find_color(pixels) {
+        Some(_) => None,
+        None if pixels.iter().all(|px| px.a == 255) => Some(GrayLayout::L),
+        None => Some(GrayLayout::LA),
+    }
+}
+
+/// Collapses gray pixels to `layout`, alpha is dropped for `GrayLayout::L`. \
+/// Fails, if a pixel isn't gray or there are fewer pixels than `width * height`.
+pub fn collapse(
+    header: &Header,
+    pixels: &[Pixel],
+    layout: GrayLayout,
+) -> Result<Vec<u8>, GrayError> {
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+    if let Some(i) = find_color(pixels) {
+        let width = header.width as usize;
+        return Err(NotGray {
+            x: (i % width) as u32,
+            y: (i / width) as u32,
+        });
+    }
+    let out = match layout {
+        GrayLayout::L => pixels.iter().map(|px| px.r).collect(),
+        GrayLayout::LA => pixels.iter().flat_map(|px| [px.r, px.a]).collect(),
+    };
+    Ok(out)
+}
+
+/// Encodes a grayscale buffer of `width x height` pixels as RGB for `GrayLayout::L` or RGBA for `GrayLayout::LA`.
+pub fn encode(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    layout: GrayLayout,
+    colorspace: ColorSpace,
+) -> Result<Vec<u8>, GrayError> {
+    let header = Header::new(width, height, layout.channels(), colorspace);
+    header.check_dimensions()?;
+    let expected_size = header.pixel_amount() * layout.bytes_per_pixel();
+    if buffer.len() != expected_size {
+        return Err(SizeMismatch {
+            expected_size,
+            received_size: buffer.len(),
+        });
+    }
+    Ok(encode_allocated(&header, &expand(buffer, layout))?)
+}
+
+/// Decodes a gray image to the smallest layout holding all of its information,
+/// `GrayLayout::LA` only if a pixel isn't opaque. \
+/// Fails with `NotGray`, if a pixel has color.
+pub fn decode(buffer: &[u8]) -> Result<(Header, GrayLayout, Vec<u8>), GrayError> {
+    let (header, pixels) = decode_allocated(buffer)?;
+    let layout = gray_layout(&pixels).unwrap_or(GrayLayout::LA);
+    let out = collapse(&header, &pixels, layout)?;
+    Ok((header, layout, out))
+}
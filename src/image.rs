@@ -0,0 +1,535 @@
+use crate::{
+    composite::Operator,
+    decode::{decode_allocated, DecodeError},
+    encode::{encode_allocated, EncodeError},
+    raw::{RawError, RawLayout},
+    transform::Transform,
+    *,
+};
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+    slice::{ChunksExact, ChunksExactMut},
+};
+
+pub enum ImageError {
+    InvalidDimensions(DimensionsError),
+
+    /// The amount of pixels doesn't match `width * height`. \
+    /// For views, there are fewer pixels than `stride * (height - 1) + width`.
+    SizeMismatch {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    /// The stride of a view is smaller than its width.
+    InvalidStride {
+        width: u32,
+        stride: usize,
+    },
+
+    /// The views passed to `copy_from` or `composite` have different dimensions.
+    DimensionMismatch {
+        first: (u32, u32),
+        second: (u32, u32),
+    },
+}
+
+impl fmt::Debug for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::SizeMismatch {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Size mismatch: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+            Self::InvalidStride { width, stride } => {
+                write!(
+                    f,
+                    "Invalid stride: {} is smaller than the width {}.",
+                    stride, width
+                )
+            }
+            Self::DimensionMismatch { first, second } => {
+                write!(
+                    f,
+                    "Dimension mismatch: {}x{} and {}x{}.",
+                    first.0, first.1, second.0, second.1
+                )
+            }
+        }
+    }
+}
+
+impl From<DimensionsError> for ImageError {
+    fn from(value: DimensionsError) -> Self {
+        ImageError::InvalidDimensions(value)
+    }
+}
+
+use ImageError::*;
+
+/// A header with valid dimensions & exactly `width * height` pixels in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    header: Header,
+    pixels: Vec<Pixel>,
+}
+
+impl Image {
+    pub fn new(header: Header, pixels: Vec<Pixel>) -> Result<Self, ImageError> {
+        header.check_dimensions()?;
+        if pixels.len() != header.pixel_amount() {
+            return Err(SizeMismatch {
+                expected_size: header.pixel_amount(),
+                received_size: pixels.len(),
+            });
+        }
+        Ok(Image { header, pixels })
+    }
+
+    /// Creates an image with every pixel set to `pixel`.
+    pub fn filled(header: Header, pixel: Pixel) -> Result<Self, ImageError> {
+        header.check_dimensions()?;
+        Ok(Image {
+            header,
+            pixels: vec![pixel; header.pixel_amount()],
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn width(&self) -> u32 {
+        self.header.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.header.height
+    }
+
+    pub fn channels(&self) -> ColorChannel {
+        self.header.channels
+    }
+
+    pub fn colorspace(&self) -> ColorSpace {
+        self.header.colorspace
+    }
+
+    /// Only changes the header, the pixels are kept as they are.
+    pub fn set_channels(&mut self, channels: ColorChannel) {
+        self.header.channels = channels;
+    }
+
+    /// Only changes the header, the pixels are kept as they are. \
+    /// Use `convert_colorspace` to convert the pixels as well.
+    pub fn set_colorspace(&mut self, colorspace: ColorSpace) {
+        self.header.colorspace = colorspace;
+    }
+
+    /// Converts the r, g & b channels to `colorspace` & updates the header.
+    pub fn convert_colorspace(&mut self, colorspace: ColorSpace) {
+        // Can't fail, since the header & pixels are checked on creation
+        color::convert_colorspace(&mut self.header, &mut self.pixels, colorspace).unwrap();
+    }
+
+    pub fn pixels(&self) -> &[Pixel] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Pixel] {
+        &mut self.pixels
+    }
+
+    /// Composites `src` onto the image with its top left corner at (x, y), see `Pixel::composite`. \
+    /// Parts of `src` outside of the image are ignored.
+    pub fn composite(&mut self, src: &ImageView, x: u32, y: u32, op: Operator) {
+        let colorspace = self.header.colorspace;
+        let width = src.width().min(self.width().saturating_sub(x));
+        let height = src.height().min(self.height().saturating_sub(y));
+        let mut view = self.view_mut();
+        let dst = view.sub_view_mut(x, y, width, height);
+        if let (Some(mut dst), Some(src)) = (dst, src.sub_view(0, 0, width, height)) {
+            // Can't fail, since both views have the same dimensions
+            dst.composite(&src, op, colorspace).unwrap();
+        }
+    }
+
+    /// Flips, rotates or transposes the image in place.
+    pub fn transform(&mut self, transform: Transform) {
+        // Can't fail, since the header & pixels are checked on creation
+        transform.apply(&mut self.header, &mut self.pixels).unwrap();
+    }
+
+    pub fn view(&self) -> ImageView<'_> {
+        ImageView {
+            pixels: &self.pixels,
+            width: self.header.width,
+            height: self.header.height,
+            stride: self.header.width as usize,
+        }
+    }
+
+    pub fn view_mut(&mut self) -> ImageViewMut<'_> {
+        ImageViewMut {
+            pixels: &mut self.pixels,
+            width: self.header.width,
+            height: self.header.height,
+            stride: self.header.width as usize,
+        }
+    }
+
+    pub fn into_parts(self) -> (Header, Vec<Pixel>) {
+        (self.header, self.pixels)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.header.width && y < self.header.height {
+            Some(y as usize * self.header.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// `None` if (x, y) is out of bounds.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Pixel> {
+        self.index(x, y).map(|i| self.pixels[i])
+    }
+
+    pub fn get_pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut Pixel> {
+        self.index(x, y).map(|i| &mut self.pixels[i])
+    }
+
+    /// Returns the replaced pixel, `None` if (x, y) is out of bounds.
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel) -> Option<Pixel> {
+        self.get_pixel_mut(x, y)
+            .map(|px| std::mem::replace(px, pixel))
+    }
+
+    pub fn rows(&self) -> ChunksExact<'_, Pixel> {
+        self.pixels.chunks_exact(self.header.width as usize)
+    }
+
+    pub fn rows_mut(&mut self) -> ChunksExactMut<'_, Pixel> {
+        self.pixels.chunks_exact_mut(self.header.width as usize)
+    }
+
+    /// Iterates over the pixels together with their coordinates (x, y).
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (u32, u32, &Pixel)> {
+        let width = self.header.width as usize;
+        self.pixels
+            .iter()
+            .enumerate()
+            .map(move |(i, px)| ((i % width) as u32, (i / width) as u32, px))
+    }
+
+    pub fn decode(buffer: &[u8]) -> Result<Self, DecodeError> {
+        let (header, pixels) = decode_allocated(buffer)?;
+        Ok(Image { header, pixels })
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        encode_allocated(&self.header, &self.pixels)
+    }
+
+    pub fn open<P>(filepath: P) -> Result<Self, DecodeError>
+    where
+        P: AsRef<Path>,
+    {
+        Image::decode(&fs::read(filepath)?)
+    }
+
+    /// Returns the size of the written file.
+    pub fn save<P>(&self, filepath: P) -> Result<usize, EncodeError>
+    where
+        P: AsRef<Path>,
+    {
+        let buffer = self.encode()?;
+        let mut file: File = open_file_w(filepath)?;
+        file.write_all(&buffer)?;
+        Ok(buffer.len())
+    }
+
+    /// Creates an image from a headerless RGB or RGBA dump, depending on `header.channels`.
+    pub fn from_raw(header: Header, buffer: &[u8]) -> Result<Self, RawError> {
+        let pixels = raw::decode(buffer, &header, RawLayout::RGBA)?;
+        Ok(Image { header, pixels })
+    }
+
+    /// Dumps the pixels as RGB or RGBA bytes, depending on the channels of the header.
+    pub fn to_raw(&self) -> Vec<u8> {
+        // Can't fail, since the header & pixels are checked on creation
+        raw::encode(&self.header, &self.pixels, RawLayout::RGBA).unwrap()
+    }
+}
+
+impl TryFrom<(Header, Vec<Pixel>)> for Image {
+    type Error = ImageError;
+    fn try_from((header, pixels): (Header, Vec<Pixel>)) -> Result<Self, Self::Error> {
+        Image::new(header, pixels)
+    }
+}
+
+impl From<Image> for (Header, Vec<Pixel>) {
+    fn from(value: Image) -> Self {
+        value.into_parts()
+    }
+}
+
+// Checks the dimensions of a view into `len` pixels
+fn check_view(len: usize, width: u32, height: u32, stride: usize) -> Result<(), ImageError> {
+    if width == 0 || height == 0 {
+        return Err(InvalidDimensions(DimensionsError { width, height }));
+    }
+    if stride < width as usize {
+        return Err(InvalidStride { width, stride });
+    }
+    let expected_size = stride
+        .saturating_mul(height as usize - 1)
+        .saturating_add(width as usize);
+    if len < expected_size {
+        return Err(SizeMismatch {
+            expected_size,
+            received_size: len,
+        });
+    }
+    Ok(())
+}
+
+// Offset of the pixel (x, y), if the region (x, y, w, h) lies within width x height
+fn sub_offset(
+    (width, height, stride): (u32, u32, usize),
+    (x, y, w, h): (u32, u32, u32, u32),
+) -> Option<usize> {
+    let fits = |pos: u32, len: u32, max: u32| {
+        len > 0 && pos.checked_add(len).is_some_and(|end| end <= max)
+    };
+    match fits(x, w, width) && fits(y, h, height) {
+        true => Some(y as usize * stride + x as usize),
+        false => None,
+    }
+}
+
+/// A rectangle of pixels, which borrows its rows from a larger buffer. \
+/// Row `y` starts at `y * stride`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageView<'a> {
+    pixels: &'a [Pixel],
+    width: u32,
+    height: u32,
+    stride: usize,
+}
+
+impl<'a> ImageView<'a> {
+    pub fn new(pixels: &'a [Pixel], width: u32, height: u32) -> Result<Self, ImageError> {
+        ImageView::with_stride(pixels, width, height, width as usize)
+    }
+
+    pub fn with_stride(
+        pixels: &'a [Pixel],
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<Self, ImageError> {
+        check_view(pixels.len(), width, height, stride)?;
+        Ok(ImageView {
+            pixels,
+            width,
+            height,
+            stride,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// `None` if (x, y) is out of bounds.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Pixel> {
+        let offset = sub_offset((self.width, self.height, self.stride), (x, y, 1, 1))?;
+        Some(self.pixels[offset])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [Pixel]> {
+        let width = self.width as usize;
+        self.pixels
+            .chunks(self.stride)
+            .take(self.height as usize)
+            .map(move |row| &row[..width])
+    }
+
+    /// The region of `w x h` pixels starting at (x, y), `None` if it's empty or doesn't fit.
+    pub fn sub_view(&self, x: u32, y: u32, w: u32, h: u32) -> Option<ImageView<'a>> {
+        let offset = sub_offset((self.width, self.height, self.stride), (x, y, w, h))?;
+        Some(ImageView {
+            pixels: &self.pixels[offset..],
+            width: w,
+            height: h,
+            stride: self.stride,
+        })
+    }
+
+    /// Copies the pixels into an owned image.
+    pub fn to_image(&self, channels: ColorChannel, colorspace: ColorSpace) -> Image {
+        Image {
+            header: Header::new(self.width, self.height, channels, colorspace),
+            pixels: self.rows().flatten().copied().collect(),
+        }
+    }
+
+    pub fn encode(
+        &self,
+        channels: ColorChannel,
+        colorspace: ColorSpace,
+    ) -> Result<Vec<u8>, EncodeError> {
+        let header = Header::new(self.width, self.height, channels, colorspace);
+        let mut buffer = vec![0; header.max_size()];
+        let size = encode::encode_view(self, channels, colorspace, &mut buffer)?;
+        buffer.truncate(size);
+        Ok(buffer)
+    }
+}
+
+/// The mutable counterpart to `ImageView`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImageViewMut<'a> {
+    pixels: &'a mut [Pixel],
+    width: u32,
+    height: u32,
+    stride: usize,
+}
+
+impl<'a> ImageViewMut<'a> {
+    pub fn new(pixels: &'a mut [Pixel], width: u32, height: u32) -> Result<Self, ImageError> {
+        ImageViewMut::with_stride(pixels, width, height, width as usize)
+    }
+
+    pub fn with_stride(
+        pixels: &'a mut [Pixel],
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<Self, ImageError> {
+        check_view(pixels.len(), width, height, stride)?;
+        Ok(ImageViewMut {
+            pixels,
+            width,
+            height,
+            stride,
+        })
+    }
+
+    pub fn as_view(&self) -> ImageView<'_> {
+        ImageView {
+            pixels: self.pixels,
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Pixel> {
+        self.as_view().get_pixel(x, y)
+    }
+
+    /// Returns the replaced pixel, `None` if (x, y) is out of bounds.
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel) -> Option<Pixel> {
+        let offset = sub_offset((self.width, self.height, self.stride), (x, y, 1, 1))?;
+        Some(std::mem::replace(&mut self.pixels[offset], pixel))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Pixel]> {
+        self.as_view().rows()
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [Pixel]> {
+        let width = self.width as usize;
+        self.pixels
+            .chunks_mut(self.stride)
+            .take(self.height as usize)
+            .map(move |row| &mut row[..width])
+    }
+
+    pub fn sub_view(&self, x: u32, y: u32, w: u32, h: u32) -> Option<ImageView<'_>> {
+        self.as_view().sub_view(x, y, w, h)
+    }
+
+    /// The region of `w x h` pixels starting at (x, y), `None` if it's empty or doesn't fit.
+    pub fn sub_view_mut(&mut self, x: u32, y: u32, w: u32, h: u32) -> Option<ImageViewMut<'_>> {
+        let offset = sub_offset((self.width, self.height, self.stride), (x, y, w, h))?;
+        Some(ImageViewMut {
+            pixels: &mut self.pixels[offset..],
+            width: w,
+            height: h,
+            stride: self.stride,
+        })
+    }
+
+    pub fn fill(&mut self, pixel: Pixel) {
+        self.rows_mut().for_each(|row| row.fill(pixel));
+    }
+
+    /// Copies the pixels of a view with the same dimensions.
+    pub fn copy_from(&mut self, src: &ImageView) -> Result<(), ImageError> {
+        if (self.width, self.height) != (src.width, src.height) {
+            return Err(DimensionMismatch {
+                first: (self.width, self.height),
+                second: (src.width, src.height),
+            });
+        }
+        for (dst, src) in self.rows_mut().zip(src.rows()) {
+            dst.copy_from_slice(src);
+        }
+        Ok(())
+    }
+
+    /// Composites the pixels of a view with the same dimensions onto this one, see `Pixel::composite`.
+    pub fn composite(
+        &mut self,
+        src: &ImageView,
+        op: Operator,
+        colorspace: ColorSpace,
+    ) -> Result<(), ImageError> {
+        if (self.width, self.height) != (src.width, src.height) {
+            return Err(DimensionMismatch {
+                first: (self.width, self.height),
+                second: (src.width, src.height),
+            });
+        }
+        for (dst, src) in self.rows_mut().zip(src.rows()) {
+            for (dst, src) in dst.iter_mut().zip(src) {
+                *dst = src.composite(*dst, op, colorspace);
+            }
+        }
+        Ok(())
+    }
+}
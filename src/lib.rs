@@ -8,8 +8,29 @@ use std::{
     path::Path,
 };
 
+pub mod asm;
+pub mod bmp;
+pub mod color;
+pub mod compare;
+pub mod composite;
+pub mod crop;
 pub mod decode;
+pub mod depth;
+pub mod disasm;
 pub mod encode;
+pub mod farbfeld;
+pub mod gray;
+pub mod image;
+pub mod png;
+pub mod pnm;
+pub mod raw;
+pub mod resize;
+pub mod scan;
+pub mod stats;
+pub mod stripe;
+pub mod tga;
+pub mod transform;
+pub mod zlib;
 pub use buf_iter::*;
 pub use util::*;
 
@@ -29,6 +50,13 @@ pub const DIFF_BIAS: u8 = 2;
 pub const LUMA_GREEN_BIAS: u8 = 32;
 pub const LUMA_BIAS: u8 = 8;
 pub const HEADER_SIZE: usize = 14;
+/// Same limit as the reference implementation, to guard against absurd allocations.
+pub const PIXELS_MAX: usize = 400_000_000;
+
+/// Rounds a 16-bit sample to the nearest 8-bit value, 8-bit values `v` are stored as `v * 257`.
+pub const fn u16_to_u8(v: u16) -> u8 {
+    ((v as u32 + 128) / 257) as u8
+}
 
 pub fn open_file_w<P>(filepath: P) -> Result<File, IOErr>
 where
@@ -49,7 +77,7 @@ where
     let mut file = File::open(filepath)?;
     let mut bytes = Vec::<u8>::new();
     file.read_to_end(&mut bytes)?;
-    decode_allocated(&mut bytes)
+    decode_allocated(&bytes)
 }
 
 pub fn write<P>(filepath: P, header: &Header, pixels: &mut [Pixel]) -> Result<usize, EncodeError>
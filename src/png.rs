@@ -0,0 +1,634 @@
+use crate::{zlib, *};
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+pub const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+const IHDR: [u8; 4] = *b"IHDR";
+const PLTE: [u8; 4] = *b"PLTE";
+const TRNS: [u8; 4] = *b"tRNS";
+const IDAT: [u8; 4] = *b"IDAT";
+const IEND: [u8; 4] = *b"IEND";
+const IDAT_SIZE: usize = 1 << 16;
+
+// Start & step of the 7 Adam7 passes: (x0, y0, dx, dy)
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+pub enum PngError {
+    /// The data doesn't start with the PNG signature.
+    InvalidSignature,
+
+    /// The data ended in the middle of a chunk or before the `IEND` chunk.
+    UnexpectedEnd,
+
+    /// The CRC of a chunk doesn't match its content.
+    /// The chunk's type is returned.
+    CrcMismatch([u8; 4]),
+
+    /// A required chunk is missing.
+    MissingChunk([u8; 4]),
+
+    /// A chunk is malformed, out of order or an unknown critical chunk.
+    InvalidChunk([u8; 4]),
+
+    /// The combination of bit depth, color type, compression, filter \
+    /// and interlace method isn't supported by PNG.
+    UnsupportedFormat {
+        bit_depth: u8,
+        color_type: u8,
+    },
+
+    InvalidDimensions(DimensionsError),
+
+    /// A scanline uses a filter type other than 0 to 4.
+    InvalidFilter(u8),
+
+    /// A pixel refers to a palette entry that doesn't exist.
+    InvalidPaletteIndex(u8),
+
+    /// The decompressed image data is too short for the image's dimensions. \
+    /// When encoding, there are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    ZlibError(zlib::ZlibError),
+
+    IOError(std::io::Error),
+}
+
+impl fmt::Debug for PngError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = |chunk: &[u8; 4]| String::from_utf8_lossy(chunk).to_string();
+        match self {
+            Self::InvalidSignature => {
+                write!(
+                    f,
+                    "Invalid signature: The data doesn't start with {:?}.",
+                    PNG_SIGNATURE
+                )
+            }
+            Self::UnexpectedEnd => write!(f, "The PNG data ended unexpectedly."),
+            Self::CrcMismatch(chunk) => write!(f, "CRC mismatch in the {} chunk.", name(chunk)),
+            Self::MissingChunk(chunk) => write!(f, "Missing the {} chunk.", name(chunk)),
+            Self::InvalidChunk(chunk) => write!(f, "Invalid {} chunk.", name(chunk)),
+            Self::UnsupportedFormat {
+                bit_depth,
+                color_type,
+            } => {
+                write!(
+                    f,
+                    "Unsupported format: bit depth {} with color type {}.",
+                    bit_depth, color_type
+                )
+            }
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::InvalidFilter(filter) => write!(f, "Invalid filter type: {}.", filter),
+            Self::InvalidPaletteIndex(index) => {
+                write!(
+                    f,
+                    "Invalid palette index: {} is outside of the palette.",
+                    index
+                )
+            }
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} bytes, instead received {} bytes.",
+                    expected_size, received_size
+                )
+            }
+            Self::ZlibError(err) => write!(f, "Zlib Error: {:?}", err),
+            Self::IOError(err) => write!(f, "IO Error: {}", err),
+        }
+    }
+}
+
+impl From<zlib::ZlibError> for PngError {
+    fn from(value: zlib::ZlibError) -> Self {
+        PngError::ZlibError(value)
+    }
+}
+
+impl From<std::io::Error> for PngError {
+    fn from(value: std::io::Error) -> Self {
+        PngError::IOError(value)
+    }
+}
+
+impl From<DimensionsError> for PngError {
+    fn from(value: DimensionsError) -> Self {
+        PngError::InvalidDimensions(value)
+    }
+}
+
+use PngError::*;
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/// CRC-32 as used by PNG chunks.
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorType {
+    Gray,
+    Rgb,
+    Palette,
+    GrayAlpha,
+    Rgba,
+}
+
+impl ColorType {
+    fn from_ihdr(color_type: u8, bit_depth: u8) -> Option<Self> {
+        match (color_type, bit_depth) {
+            (0, 1 | 2 | 4 | 8 | 16) => Some(ColorType::Gray),
+            (2, 8 | 16) => Some(ColorType::Rgb),
+            (3, 1 | 2 | 4 | 8) => Some(ColorType::Palette),
+            (4, 8 | 16) => Some(ColorType::GrayAlpha),
+            (6, 8 | 16) => Some(ColorType::Rgba),
+            _ => None,
+        }
+    }
+
+    fn samples(&self) -> usize {
+        match self {
+            ColorType::Gray | ColorType::Palette => 1,
+            ColorType::GrayAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+}
+
+struct Ihdr {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: ColorType,
+    interlaced: bool,
+}
+
+impl Ihdr {
+    fn bits_per_pixel(&self) -> usize {
+        self.color_type.samples() * self.bit_depth as usize
+    }
+
+    // Byte distance to the corresponding byte of the previous pixel, used by the filters
+    fn filter_bpp(&self) -> usize {
+        self.bits_per_pixel().div_ceil(8)
+    }
+
+    fn row_bytes(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+fn parse_ihdr(data: &[u8]) -> Result<Ihdr, PngError> {
+    if data.len() != 13 {
+        return Err(InvalidChunk(IHDR));
+    }
+    let width = u32::from_be_bytes(data[0..4].try_into().unwrap());
+    let height = u32::from_be_bytes(data[4..8].try_into().unwrap());
+    let (bit_depth, color_type) = (data[8], data[9]);
+    let unsupported = UnsupportedFormat {
+        bit_depth,
+        color_type,
+    };
+    let color_type = ColorType::from_ihdr(color_type, bit_depth).ok_or(unsupported)?;
+    if data[10] != 0 || data[11] != 0 || data[12] > 1 {
+        return Err(InvalidChunk(IHDR));
+    }
+    let header = Header::new(width, height, ColorChannel::RGBA, ColorSpace::SRGB);
+    header.check_dimensions()?;
+    Ok(Ihdr {
+        width,
+        height,
+        bit_depth,
+        color_type,
+        interlaced: data[12] == 1,
+    })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Reverses the filter of `row` in place, `prev` is the already unfiltered previous row
+fn unfilter(filter: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<(), PngError> {
+    match filter {
+        0 => {}
+        1 => {
+            for i in bpp..row.len() {
+                row[i] = row[i].wrapping_add(row[i - bpp]);
+            }
+        }
+        2 => {
+            for (byte, &up) in row.iter_mut().zip(prev) {
+                *byte = byte.wrapping_add(up);
+            }
+        }
+        3 => {
+            for i in 0..row.len() {
+                let left = if i >= bpp { row[i - bpp] } else { 0 };
+                row[i] = row[i].wrapping_add(((left as u16 + prev[i] as u16) / 2) as u8);
+            }
+        }
+        4 => {
+            for i in 0..row.len() {
+                let (left, up_left) = if i >= bpp {
+                    (row[i - bpp], prev[i - bpp])
+                } else {
+                    (0, 0)
+                };
+                row[i] = row[i].wrapping_add(paeth(left, prev[i], up_left));
+            }
+        }
+        _ => return Err(InvalidFilter(filter)),
+    }
+    Ok(())
+}
+
+// Reads the `idx`-th sample of a row, scaled to 16 bit for depths of 16, otherwise kept as is
+fn sample(row: &[u8], idx: usize, bit_depth: u8) -> u16 {
+    match bit_depth {
+        16 => u16::from_be_bytes([row[idx * 2], row[idx * 2 + 1]]),
+        8 => row[idx] as u16,
+        _ => {
+            let bits = bit_depth as usize;
+            let per_byte = 8 / bits;
+            let shift = 8 - bits * (idx % per_byte + 1);
+            ((row[idx / per_byte] >> shift) & ((1 << bits) - 1) as u8) as u16
+        }
+    }
+}
+
+struct PixelReader<'a> {
+    ihdr: &'a Ihdr,
+    palette: &'a [Pixel],
+    // Raw sample values that are fully transparent
+    transparent: Option<[u16; 3]>,
+}
+
+impl PixelReader<'_> {
+    fn scale(&self, val: u16) -> u8 {
+        match self.ihdr.bit_depth {
+            16 => u16_to_u8(val),
+            8 => val as u8,
+            depth => (val as u32 * 255 / ((1 << depth) - 1)) as u8,
+        }
+    }
+
+    fn read_row(
+        &self,
+        row: &[u8],
+        width: usize,
+        out: &mut [Pixel],
+        x0: usize,
+        dx: usize,
+    ) -> Result<(), PngError> {
+        let depth = self.ihdr.bit_depth;
+        let samples = self.ihdr.color_type.samples();
+        for x in 0..width {
+            let s = |i: usize| sample(row, x * samples + i, depth);
+            let px = match self.ihdr.color_type {
+                ColorType::Palette => {
+                    let idx = s(0);
+                    *self
+                        .palette
+                        .get(idx as usize)
+                        .ok_or(InvalidPaletteIndex(idx as u8))?
+                }
+                ColorType::Gray => {
+                    let gray = self.scale(s(0));
+                    let transparent = self.transparent.is_some_and(|t| t[0] == s(0));
+                    Pixel {
+                        r: gray,
+                        g: gray,
+                        b: gray,
+                        a: if transparent { 0 } else { 255 },
+                    }
+                }
+                ColorType::GrayAlpha => {
+                    let gray = self.scale(s(0));
+                    Pixel {
+                        r: gray,
+                        g: gray,
+                        b: gray,
+                        a: self.scale(s(1)),
+                    }
+                }
+                ColorType::Rgb => {
+                    let raw = [s(0), s(1), s(2)];
+                    let transparent = self.transparent.is_some_and(|t| t == raw);
+                    Pixel {
+                        r: self.scale(raw[0]),
+                        g: self.scale(raw[1]),
+                        b: self.scale(raw[2]),
+                        a: if transparent { 0 } else { 255 },
+                    }
+                }
+                ColorType::Rgba => Pixel {
+                    r: self.scale(s(0)),
+                    g: self.scale(s(1)),
+                    b: self.scale(s(2)),
+                    a: self.scale(s(3)),
+                },
+            };
+            out[x0 + x * dx] = px;
+        }
+        Ok(())
+    }
+}
+
+struct Chunk<'a> {
+    kind: [u8; 4],
+    data: &'a [u8],
+}
+
+fn read_chunk<'a>(buffer: &'a [u8], pos: &mut usize) -> Result<Chunk<'a>, PngError> {
+    let mut read = |size: usize| match buffer.get(*pos..pos.checked_add(size)?) {
+        None => None,
+        Some(bytes) => {
+            *pos += size;
+            Some(bytes)
+        }
+    };
+    let len = read(4).ok_or(UnexpectedEnd)?;
+    let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+    let content = read(len.checked_add(4).ok_or(UnexpectedEnd)?).ok_or(UnexpectedEnd)?;
+    let crc = read(4).ok_or(UnexpectedEnd)?;
+    let kind: [u8; 4] = content[..4].try_into().unwrap();
+    if crc32(content) != u32::from_be_bytes(crc.try_into().unwrap()) {
+        return Err(CrcMismatch(kind));
+    }
+    Ok(Chunk {
+        kind,
+        data: &content[4..],
+    })
+}
+
+/// Decodes a PNG image. Images with an alpha channel or transparency are returned as RGBA, others as RGB.
+/// Samples with 16 bits are rounded to 8 bits.
+pub fn decode(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), PngError> {
+    if !buffer.starts_with(&PNG_SIGNATURE) {
+        return Err(InvalidSignature);
+    }
+    let mut pos = PNG_SIGNATURE.len();
+
+    let chunk = read_chunk(buffer, &mut pos)?;
+    if chunk.kind != IHDR {
+        return Err(MissingChunk(IHDR));
+    }
+    let ihdr = parse_ihdr(chunk.data)?;
+
+    let mut palette = Vec::new();
+    let mut transparent = None;
+    let mut has_trns = false;
+    let mut idat = Vec::new();
+    loop {
+        let chunk = read_chunk(buffer, &mut pos)?;
+        match chunk.kind {
+            IEND => break,
+            PLTE => {
+                if chunk.data.len() % 3 != 0 || chunk.data.len() > 3 * 256 || !idat.is_empty() {
+                    return Err(InvalidChunk(PLTE));
+                }
+                palette = chunk.data.chunks(3).map(Pixel::from).collect();
+            }
+            TRNS => {
+                has_trns = true;
+                let data = chunk.data;
+                let be = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+                match (ihdr.color_type, data.len()) {
+                    (ColorType::Palette, len) if len <= palette.len() => {
+                        for (px, &alpha) in palette.iter_mut().zip(data) {
+                            px.a = alpha;
+                        }
+                    }
+                    (ColorType::Gray, 2) => transparent = Some([be(0); 3]),
+                    (ColorType::Rgb, 6) => transparent = Some([be(0), be(2), be(4)]),
+                    _ => return Err(InvalidChunk(TRNS)),
+                }
+            }
+            IDAT => idat.extend_from_slice(chunk.data),
+            IHDR => return Err(InvalidChunk(IHDR)),
+            // Ancillary chunks start with a lower case letter, critical ones need to be understood
+            kind if kind[0].is_ascii_uppercase() => return Err(InvalidChunk(kind)),
+            _ => {}
+        }
+    }
+    if idat.is_empty() {
+        return Err(MissingChunk(IDAT));
+    }
+    if ihdr.color_type == ColorType::Palette && palette.is_empty() {
+        return Err(MissingChunk(PLTE));
+    }
+
+    let (width, height) = (ihdr.width as usize, ihdr.height as usize);
+    let passes: &[(usize, usize, usize, usize)] = if ihdr.interlaced {
+        &ADAM7
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+    let pass_size = |&(x0, y0, dx, dy): &(usize, usize, usize, usize)| {
+        ((width + dx - 1 - x0) / dx, (height + dy - 1 - y0) / dy)
+    };
+    // Every scanline of every pass starts with its filter type
+    let raw_size = passes
+        .iter()
+        .map(pass_size)
+        .filter(|&(pass_width, pass_height)| pass_width > 0 && pass_height > 0)
+        .map(|(pass_width, pass_height)| pass_height * (ihdr.row_bytes(pass_width) + 1))
+        .sum();
+    let data = zlib::decompress(&idat, raw_size)?;
+    let reader = PixelReader {
+        ihdr: &ihdr,
+        palette: &palette,
+        transparent,
+    };
+    let mut pixels = vec![Pixel::def(); width * height];
+
+    let bpp = ihdr.filter_bpp();
+    let mut data = BufIter::new(&data);
+    for pass @ &(x0, y0, dx, dy) in passes {
+        let (pass_width, pass_height) = pass_size(pass);
+        if pass_width == 0 || pass_height == 0 {
+            continue;
+        }
+        let row_bytes = ihdr.row_bytes(pass_width);
+        let mut prev = vec![0u8; row_bytes];
+        let mut row = vec![0u8; row_bytes];
+        for y in 0..pass_height {
+            let line = match data.step_forward(row_bytes + 1) {
+                None => Err(MissingPixels {
+                    expected_size: data.idx() + row_bytes + 1,
+                    received_size: data.len(),
+                }),
+                Some(line) => Ok(line),
+            }?;
+            row.copy_from_slice(&line[1..]);
+            unfilter(line[0], &mut row, &prev, bpp)?;
+            let start = (y0 + y * dy) * width;
+            reader.read_row(&row, pass_width, &mut pixels[start..start + width], x0, dx)?;
+            std::mem::swap(&mut row, &mut prev);
+        }
+    }
+
+    let has_alpha = matches!(ihdr.color_type, ColorType::GrayAlpha | ColorType::Rgba) || has_trns;
+    let channels = if has_alpha {
+        ColorChannel::RGBA
+    } else {
+        ColorChannel::RGB
+    };
+    let header = Header::new(ihdr.width, ihdr.height, channels, ColorSpace::SRGB);
+    Ok((header, pixels))
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: [u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(&kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Filters `row` with the filter type, that results in the lowest sum of absolute differences
+fn filter_row(row: &[u8], prev: &[u8], bpp: usize, out: &mut Vec<u8>) {
+    let mut best: Option<(u64, u8, Vec<u8>)> = None;
+    for filter in 0..5u8 {
+        let filtered: Vec<u8> = (0..row.len())
+            .map(|i| {
+                let left = if i >= bpp { row[i - bpp] } else { 0 };
+                let up_left = if i >= bpp { prev[i - bpp] } else { 0 };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => prev[i],
+                    3 => ((left as u16 + prev[i] as u16) / 2) as u8,
+                    _ => paeth(left, prev[i], up_left),
+                };
+                row[i].wrapping_sub(predicted)
+            })
+            .collect();
+        let cost = filtered
+            .iter()
+            .map(|&byte| (byte as i8).unsigned_abs() as u64)
+            .sum();
+        if best
+            .as_ref()
+            .is_none_or(|(best_cost, _, _)| cost < *best_cost)
+        {
+            best = Some((cost, filter, filtered));
+        }
+    }
+    let (_, filter, filtered) = best.unwrap();
+    out.push(filter);
+    out.extend(filtered);
+}
+
+/// Encodes an image as 8-bit RGB or RGBA PNG, depending on `header.channels`.
+pub fn encode(header: &Header, pixels: &[Pixel]) -> Result<Vec<u8>, PngError> {
+    header.check_dimensions()?;
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+
+    let bpp = header.bytes_per_pixel();
+    let row_bytes = header.width as usize * bpp;
+    let mut raw = Vec::with_capacity((row_bytes + 1) * header.height as usize);
+    let mut prev = vec![0u8; row_bytes];
+    let mut row = Vec::with_capacity(row_bytes);
+    for line in pixels.chunks(header.width as usize) {
+        row.clear();
+        for px in line {
+            row.extend_from_slice(&[px.r, px.g, px.b, px.a][..bpp]);
+        }
+        filter_row(&row, &prev, bpp, &mut raw);
+        std::mem::swap(&mut row, &mut prev);
+    }
+
+    let mut out = PNG_SIGNATURE.to_vec();
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&header.width.to_be_bytes());
+    ihdr.extend_from_slice(&header.height.to_be_bytes());
+    let color_type = match header.channels {
+        ColorChannel::RGB => 2,
+        ColorChannel::RGBA => 6,
+    };
+    ihdr.extend_from_slice(&[8, color_type, 0, 0, 0]);
+    write_chunk(&mut out, IHDR, &ihdr);
+    for data in zlib::compress(&raw).chunks(IDAT_SIZE) {
+        write_chunk(&mut out, IDAT, data);
+    }
+    write_chunk(&mut out, IEND, &[]);
+    Ok(out)
+}
+
+pub fn read<P>(filepath: P) -> Result<(Header, Vec<Pixel>), PngError>
+where
+    P: AsRef<Path>,
+{
+    decode(&fs::read(filepath)?)
+}
+
+pub fn write<P>(filepath: P, header: &Header, pixels: &[Pixel]) -> Result<usize, PngError>
+where
+    P: AsRef<Path>,
+{
+    let buffer = encode(header, pixels)?;
+    let mut file: File = open_file_w(filepath)?;
+    file.write_all(&buffer)?;
+    Ok(buffer.len())
+}
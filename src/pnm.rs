@@ -0,0 +1,384 @@
+use crate::*;
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+/// Netpbm formats that can be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PnmFormat {
+    /// Binary PGM (P5), pixels are converted to their luma, alpha is dropped.
+    Pgm,
+    /// Binary PPM (P6), alpha is dropped.
+    Ppm,
+    /// PAM (P7) with the tuple type `RGB` or `RGB_ALPHA`, depending on `header.channels`.
+    Pam,
+}
+
+pub enum PnmError {
+    /// The data doesn't start with P5, P6 or P7.
+    InvalidMagic,
+
+    /// A header field is missing, malformed or out of range.
+    InvalidHeader,
+
+    /// The PAM tuple type isn't supported or doesn't match the depth.
+    UnsupportedTupleType(String),
+
+    InvalidDimensions(DimensionsError),
+
+    /// A sample is greater than the image's maxval.
+    SampleOutOfRange {
+        sample: u16,
+        maxval: u16,
+    },
+
+    /// The raster is too short for the image's dimensions. \
+    /// When encoding, there are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    IOError(std::io::Error),
+}
+
+impl fmt::Debug for PnmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => {
+                write!(
+                    f,
+                    "Invalid magic: The data doesn't start with P5, P6 or P7."
+                )
+            }
+            Self::InvalidHeader => write!(f, "The header is missing a field or is malformed."),
+            Self::UnsupportedTupleType(tuple_type) => {
+                write!(f, "Unsupported tuple type: {}.", tuple_type)
+            }
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::SampleOutOfRange { sample, maxval } => {
+                write!(
+                    f,
+                    "Sample out of range: {} is greater than the maxval {}.",
+                    sample, maxval
+                )
+            }
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} bytes, instead received {} bytes.",
+                    expected_size, received_size
+                )
+            }
+            Self::IOError(err) => write!(f, "IO Error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for PnmError {
+    fn from(value: std::io::Error) -> Self {
+        PnmError::IOError(value)
+    }
+}
+
+impl From<DimensionsError> for PnmError {
+    fn from(value: DimensionsError) -> Self {
+        PnmError::InvalidDimensions(value)
+    }
+}
+
+use PnmError::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TupleType {
+    Gray,
+    GrayAlpha,
+    Rgb,
+    Rgba,
+}
+
+impl TupleType {
+    fn depth(&self) -> usize {
+        match self {
+            TupleType::Gray => 1,
+            TupleType::GrayAlpha => 2,
+            TupleType::Rgb => 3,
+            TupleType::Rgba => 4,
+        }
+    }
+}
+
+struct PnmHeader {
+    width: u32,
+    height: u32,
+    maxval: u16,
+    tuple_type: TupleType,
+}
+
+// Splits the header of P5 & P6 files into whitespace separated tokens, skipping comments
+struct Tokens<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next_token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            match self.buffer.get(self.pos)? {
+                b'#' => {
+                    while *self.buffer.get(self.pos)? != b'\n' {
+                        self.pos += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        while self
+            .buffer
+            .get(self.pos)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+        Some(&self.buffer[start..self.pos])
+    }
+
+    fn next_number<T: std::str::FromStr>(&mut self) -> Result<T, PnmError> {
+        let token = self.next_token().ok_or(InvalidHeader)?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or(InvalidHeader)
+    }
+}
+
+fn parse_maxval(maxval: u32) -> Result<u16, PnmError> {
+    match maxval {
+        1..=65535 => Ok(maxval as u16),
+        _ => Err(InvalidHeader),
+    }
+}
+
+// Returns the header & the offset of the raster
+fn parse_pnm_header(buffer: &[u8], tuple_type: TupleType) -> Result<(PnmHeader, usize), PnmError> {
+    let mut tokens = Tokens { buffer, pos: 2 };
+    let width = tokens.next_number()?;
+    let height = tokens.next_number()?;
+    let maxval = parse_maxval(tokens.next_number()?)?;
+    // A single whitespace character separates the header from the raster
+    match buffer.get(tokens.pos) {
+        Some(byte) if byte.is_ascii_whitespace() => {}
+        _ => return Err(InvalidHeader),
+    }
+    let header = PnmHeader {
+        width,
+        height,
+        maxval,
+        tuple_type,
+    };
+    Ok((header, tokens.pos + 1))
+}
+
+fn parse_pam_header(buffer: &[u8]) -> Result<(PnmHeader, usize), PnmError> {
+    let (mut width, mut height, mut depth, mut maxval) = (None, None, None, None);
+    let mut tuple_type = None;
+    let mut pos = 2;
+    loop {
+        let end = buffer[pos..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or(InvalidHeader)?;
+        let line = std::str::from_utf8(&buffer[pos..pos + end]).map_err(|_| InvalidHeader)?;
+        pos += end + 1;
+
+        let line = line.trim();
+        let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let number = |value: &str| value.trim().parse::<u32>().map_err(|_| InvalidHeader);
+        match key {
+            "" => {}
+            _ if key.starts_with('#') => {}
+            "WIDTH" => width = Some(number(value)?),
+            "HEIGHT" => height = Some(number(value)?),
+            "DEPTH" => depth = Some(number(value)?),
+            "MAXVAL" => maxval = Some(parse_maxval(number(value)?)?),
+            "TUPLTYPE" => tuple_type = Some(value.trim().to_string()),
+            "ENDHDR" => break,
+            _ => return Err(InvalidHeader),
+        }
+    }
+
+    let (width, height) = width.zip(height).ok_or(InvalidHeader)?;
+    let depth = depth.ok_or(InvalidHeader)?;
+    let maxval = maxval.ok_or(InvalidHeader)?;
+    let tuple_type = match tuple_type.as_deref() {
+        None => match depth {
+            1 => TupleType::Gray,
+            2 => TupleType::GrayAlpha,
+            3 => TupleType::Rgb,
+            4 => TupleType::Rgba,
+            _ => return Err(UnsupportedTupleType(format!("depth {}", depth))),
+        },
+        Some("BLACKANDWHITE" | "GRAYSCALE") => TupleType::Gray,
+        Some("BLACKANDWHITE_ALPHA" | "GRAYSCALE_ALPHA") => TupleType::GrayAlpha,
+        Some("RGB") => TupleType::Rgb,
+        Some("RGB_ALPHA") => TupleType::Rgba,
+        Some(other) => return Err(UnsupportedTupleType(other.to_string())),
+    };
+    if tuple_type.depth() != depth as usize {
+        return Err(UnsupportedTupleType(format!(
+            "{:?} with depth {}",
+            tuple_type, depth
+        )));
+    }
+    let header = PnmHeader {
+        width,
+        height,
+        maxval,
+        tuple_type,
+    };
+    Ok((header, pos))
+}
+
+/// Decodes a binary PGM (P5), PPM (P6) or PAM (P7) image. \
+/// Images with an alpha channel are returned as RGBA, others as RGB. \
+/// Samples with a maxval other than 255 are scaled to 8 bits with rounding.
+pub fn decode(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), PnmError> {
+    let (pnm, offset) = match buffer.get(..2) {
+        Some(b"P5") => parse_pnm_header(buffer, TupleType::Gray)?,
+        Some(b"P6") => parse_pnm_header(buffer, TupleType::Rgb)?,
+        Some(b"P7") => parse_pam_header(buffer)?,
+        _ => return Err(InvalidMagic),
+    };
+    let channels = match pnm.tuple_type {
+        TupleType::GrayAlpha | TupleType::Rgba => ColorChannel::RGBA,
+        TupleType::Gray | TupleType::Rgb => ColorChannel::RGB,
+    };
+    let header = Header::new(pnm.width, pnm.height, channels, ColorSpace::SRGB);
+    header.check_dimensions()?;
+
+    let sample_size = if pnm.maxval > 255 { 2 } else { 1 };
+    let pixel_size = pnm.tuple_type.depth() * sample_size;
+    let expected_size = header.pixel_amount() * pixel_size;
+    let raster = match buffer[offset..].get(..expected_size) {
+        None => Err(MissingPixels {
+            expected_size,
+            received_size: buffer.len() - offset,
+        }),
+        Some(raster) => Ok(raster),
+    }?;
+
+    let maxval = pnm.maxval as u32;
+    let sample = |bytes: &[u8], i: usize| -> Result<u8, PnmError> {
+        let val = match sample_size {
+            2 => u16::from_be_bytes([bytes[2 * i], bytes[2 * i + 1]]),
+            _ => bytes[i] as u16,
+        };
+        if val as u32 > maxval {
+            return Err(SampleOutOfRange {
+                sample: val,
+                maxval: pnm.maxval,
+            });
+        }
+        Ok(((val as u32 * 255 + maxval / 2) / maxval) as u8)
+    };
+
+    let mut pixels = Vec::with_capacity(header.pixel_amount());
+    for bytes in raster.chunks(pixel_size) {
+        let px = match pnm.tuple_type {
+            TupleType::Gray => {
+                let gray = sample(bytes, 0)?;
+                Pixel::from([gray, gray, gray])
+            }
+            TupleType::GrayAlpha => {
+                let gray = sample(bytes, 0)?;
+                Pixel::from([gray, gray, gray, sample(bytes, 1)?])
+            }
+            TupleType::Rgb => {
+                Pixel::from([sample(bytes, 0)?, sample(bytes, 1)?, sample(bytes, 2)?])
+            }
+            TupleType::Rgba => Pixel::from([
+                sample(bytes, 0)?,
+                sample(bytes, 1)?,
+                sample(bytes, 2)?,
+                sample(bytes, 3)?,
+            ]),
+        };
+        pixels.push(px);
+    }
+    Ok((header, pixels))
+}
+
+// Rec. 601 luma, as used by ppmtopgm
+fn luma(px: &Pixel) -> u8 {
+    ((px.r as u32 * 299 + px.g as u32 * 587 + px.b as u32 * 114 + 500) / 1000) as u8
+}
+
+/// Encodes an image with 8-bit samples in the given format.
+pub fn encode(header: &Header, pixels: &[Pixel], format: PnmFormat) -> Result<Vec<u8>, PnmError> {
+    header.check_dimensions()?;
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+
+    let (width, height) = (header.width, header.height);
+    let mut out = match format {
+        PnmFormat::Pgm => format!("P5\n{} {}\n255\n", width, height),
+        PnmFormat::Ppm => format!("P6\n{} {}\n255\n", width, height),
+        PnmFormat::Pam => {
+            let (depth, tuple_type) = match header.channels {
+                ColorChannel::RGB => (3, "RGB"),
+                ColorChannel::RGBA => (4, "RGB_ALPHA"),
+            };
+            format!(
+                "P7\nWIDTH {}\nHEIGHT {}\nDEPTH {}\nMAXVAL 255\nTUPLTYPE {}\nENDHDR\n",
+                width, height, depth, tuple_type
+            )
+        }
+    }
+    .into_bytes();
+
+    for px in pixels {
+        match (format, header.channels) {
+            (PnmFormat::Pgm, _) => out.push(luma(px)),
+            (PnmFormat::Pam, ColorChannel::RGBA) => {
+                out.extend_from_slice(&[px.r, px.g, px.b, px.a])
+            }
+            _ => out.extend_from_slice(&[px.r, px.g, px.b]),
+        }
+    }
+    Ok(out)
+}
+
+pub fn read<P>(filepath: P) -> Result<(Header, Vec<Pixel>), PnmError>
+where
+    P: AsRef<Path>,
+{
+    decode(&fs::read(filepath)?)
+}
+
+pub fn write<P>(
+    filepath: P,
+    header: &Header,
+    pixels: &[Pixel],
+    format: PnmFormat,
+) -> Result<usize, PnmError>
+where
+    P: AsRef<Path>,
+{
+    let buffer = encode(header, pixels, format)?;
+    let mut file: File = open_file_w(filepath)?;
+    file.write_all(&buffer)?;
+    Ok(buffer.len())
+}
@@ -0,0 +1,162 @@
+use crate::*;
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+/// Order of the channels within a pixel of a headerless dump. \
+/// With 3 channels the alpha channel is left out, e.g. `ARGB` is stored as RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RawLayout {
+    #[default]
+    RGBA,
+    BGRA,
+    ARGB,
+    ABGR,
+}
+
+impl RawLayout {
+    // Position of r, g, b & a within a pixel, alpha is out of range for 3 channels
+    fn offsets(&self, channels: ColorChannel) -> [usize; 4] {
+        match (self, channels) {
+            (RawLayout::RGBA, _) | (RawLayout::ARGB, ColorChannel::RGB) => [0, 1, 2, 3],
+            (RawLayout::BGRA, _) | (RawLayout::ABGR, ColorChannel::RGB) => [2, 1, 0, 3],
+            (RawLayout::ARGB, ColorChannel::RGBA) => [1, 2, 3, 0],
+            (RawLayout::ABGR, ColorChannel::RGBA) => [3, 2, 1, 0],
+        }
+    }
+}
+
+pub enum RawError {
+    InvalidDimensions(DimensionsError),
+
+    /// The size of the dump doesn't match the dimensions & channels.
+    SizeMismatch {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    /// When encoding, there are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    IOError(std::io::Error),
+}
+
+impl fmt::Debug for RawError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::SizeMismatch {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Size mismatch: Expected {} bytes, instead received {} bytes.",
+                    expected_size, received_size
+                )
+            }
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+            Self::IOError(err) => write!(f, "IO Error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for RawError {
+    fn from(value: std::io::Error) -> Self {
+        RawError::IOError(value)
+    }
+}
+
+impl From<DimensionsError> for RawError {
+    fn from(value: DimensionsError) -> Self {
+        RawError::InvalidDimensions(value)
+    }
+}
+
+use RawError::*;
+
+/// Decodes a headerless dump with the dimensions & channels of `header`. \
+/// Pixels of dumps with 3 channels are opaque.
+pub fn decode(buffer: &[u8], header: &Header, layout: RawLayout) -> Result<Vec<Pixel>, RawError> {
+    header.check_dimensions()?;
+    if buffer.len() != header.pixel_len() {
+        return Err(SizeMismatch {
+            expected_size: header.pixel_len(),
+            received_size: buffer.len(),
+        });
+    }
+
+    let [r, g, b, a] = layout.offsets(header.channels);
+    let pixels = buffer
+        .chunks(header.bytes_per_pixel())
+        .map(|bytes| Pixel {
+            r: bytes[r],
+            g: bytes[g],
+            b: bytes[b],
+            a: bytes.get(a).copied().unwrap_or(255),
+        })
+        .collect();
+    Ok(pixels)
+}
+
+/// Encodes the pixels as a headerless dump with the channels of `header`.
+pub fn encode(header: &Header, pixels: &[Pixel], layout: RawLayout) -> Result<Vec<u8>, RawError> {
+    header.check_dimensions()?;
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+
+    let bpp = header.bytes_per_pixel();
+    let [r, g, b, a] = layout.offsets(header.channels);
+    let mut out = vec![0; header.pixel_len()];
+    for (bytes, px) in out.chunks_mut(bpp).zip(pixels) {
+        bytes[r] = px.r;
+        bytes[g] = px.g;
+        bytes[b] = px.b;
+        if let Some(alpha) = bytes.get_mut(a) {
+            *alpha = px.a;
+        }
+    }
+    Ok(out)
+}
+
+pub fn read<P>(filepath: P, header: &Header, layout: RawLayout) -> Result<Vec<Pixel>, RawError>
+where
+    P: AsRef<Path>,
+{
+    decode(&fs::read(filepath)?, header, layout)
+}
+
+pub fn write<P>(
+    filepath: P,
+    header: &Header,
+    pixels: &[Pixel],
+    layout: RawLayout,
+) -> Result<usize, RawError>
+where
+    P: AsRef<Path>,
+{
+    let buffer = encode(header, pixels, layout)?;
+    let mut file: File = open_file_w(filepath)?;
+    file.write_all(&buffer)?;
+    Ok(buffer.len())
+}
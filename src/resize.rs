@@ -0,0 +1,238 @@
+use crate::{color::*, *};
+use std::{f32::consts::PI, fmt};
+
+/// Resampling filter used by `resize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filter {
+    /// Picks the closest source pixel, the only filter that doesn't create new colors.
+    Nearest,
+    Bilinear,
+    /// Averages the covered source pixels, best suited for shrinking by integer factors.
+    Box,
+    /// Sharpest of the filters, but may cause slight ringing at hard edges.
+    #[default]
+    Lanczos3,
+}
+
+impl Filter {
+    fn radius(&self) -> f32 {
+        match self {
+            Filter::Nearest | Filter::Box => 0.5,
+            Filter::Bilinear => 1.0,
+            Filter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn weight(&self, x: f32) -> f32 {
+        let sinc = |x: f32| match x == 0.0 {
+            true => 1.0,
+            false => (PI * x).sin() / (PI * x),
+        };
+        match self {
+            Filter::Nearest | Filter::Box => match (-0.5..0.5).contains(&x) {
+                true => 1.0,
+                false => 0.0,
+            },
+            Filter::Bilinear => (1.0 - x.abs()).max(0.0),
+            Filter::Lanczos3 => match x.abs() < 3.0 {
+                true => sinc(x) * sinc(x / 3.0),
+                false => 0.0,
+            },
+        }
+    }
+}
+
+pub enum ResizeError {
+    /// The source or target image is empty or has more than `PIXELS_MAX` pixels.
+    InvalidDimensions(DimensionsError),
+
+    /// There are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+}
+
+impl fmt::Debug for ResizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+        }
+    }
+}
+
+impl From<DimensionsError> for ResizeError {
+    fn from(value: DimensionsError) -> Self {
+        ResizeError::InvalidDimensions(value)
+    }
+}
+
+use ResizeError::*;
+
+// Source pixels & their weights contributing to one target pixel
+struct Contribution {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+fn contributions(src_len: usize, dst_len: usize, filter: Filter) -> Vec<Contribution> {
+    let ratio = src_len as f32 / dst_len as f32;
+    // When shrinking, the filter is stretched to cover all source pixels
+    let scale = ratio.max(1.0);
+    let support = filter.radius() * scale;
+    (0..dst_len)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * ratio - 0.5;
+            let left = (center - support).ceil().max(0.0) as usize;
+            let right = ((center + support).floor() as usize).min(src_len - 1);
+            let mut weights: Vec<f32> = (left..=right)
+                .map(|j| filter.weight((j as f32 - center) / scale))
+                .collect();
+            let sum: f32 = weights.iter().sum();
+            if sum != 0.0 {
+                weights.iter_mut().for_each(|w| *w /= sum);
+            }
+            Contribution {
+                start: left,
+                weights,
+            }
+        })
+        .collect()
+}
+
+// Converts to premultiplied floats, in linear light for sRGB images
+fn to_linear(pixels: &[Pixel], colorspace: ColorSpace) -> Vec<[f32; 4]> {
+    let table: [f32; 256] = match colorspace {
+        ColorSpace::SRGB => *srgb_to_linear_table(),
+        ColorSpace::LINEAR => std::array::from_fn(|v| v as f32 / 255.0),
+    };
+    pixels
+        .iter()
+        .map(|px| {
+            let a = px.a as f32 / 255.0;
+            [
+                table[px.r as usize] * a,
+                table[px.g as usize] * a,
+                table[px.b as usize] * a,
+                a,
+            ]
+        })
+        .collect()
+}
+
+fn from_linear(values: &[[f32; 4]], colorspace: ColorSpace) -> Vec<Pixel> {
+    let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    values
+        .iter()
+        .map(|&[r, g, b, a]| {
+            let a = a.clamp(0.0, 1.0);
+            if a == 0.0 {
+                return Pixel::zero();
+            }
+            let channel = |v: f32| {
+                let v = (v / a).clamp(0.0, 1.0);
+                to_u8(match colorspace {
+                    ColorSpace::SRGB => linear_to_srgb_f32(v),
+                    ColorSpace::LINEAR => v,
+                })
+            };
+            Pixel {
+                r: channel(r),
+                g: channel(g),
+                b: channel(b),
+                a: to_u8(a),
+            }
+        })
+        .collect()
+}
+
+// Resamples `lines` lines of `src_len` values, whose elements are `step` apart
+fn resample(
+    src: &[[f32; 4]],
+    contributions: &[Contribution],
+    lines: usize,
+    (line_step, step): (usize, usize),
+) -> Vec<[f32; 4]> {
+    let mut dst = Vec::with_capacity(lines * contributions.len());
+    for line in 0..lines {
+        for c in contributions {
+            let mut sum = [0.0; 4];
+            for (k, &w) in c.weights.iter().enumerate() {
+                let px = src[line * line_step + (c.start + k) * step];
+                for (s, v) in sum.iter_mut().zip(px) {
+                    *s += v * w;
+                }
+            }
+            dst.push(sum);
+        }
+    }
+    dst
+}
+
+/// Resizes the image to `width x height` pixels. \
+/// Except for `Filter::Nearest`, the pixels are filtered with premultiplied alpha,
+/// so that transparent pixels don't bleed into their neighbours,
+/// and in linear light for `ColorSpace::SRGB`.
+pub fn resize(
+    header: &Header,
+    pixels: &[Pixel],
+    width: u32,
+    height: u32,
+    filter: Filter,
+) -> Result<(Header, Vec<Pixel>), ResizeError> {
+    let target = Header {
+        width,
+        height,
+        ..*header
+    };
+    for header in [header, &target] {
+        header.check_dimensions()?;
+    }
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+    let (src_width, src_height) = (header.width as usize, header.height as usize);
+    let (dst_width, dst_height) = (width as usize, height as usize);
+
+    if filter == Filter::Nearest {
+        // Source pixel under the center of the target pixel
+        let pick = |src_len: usize, dst_len: usize, i: usize| (2 * i + 1) * src_len / (2 * dst_len);
+        let res = (0..dst_height)
+            .flat_map(|y| (0..dst_width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (x, y) = (
+                    pick(src_width, dst_width, x),
+                    pick(src_height, dst_height, y),
+                );
+                pixels[y * src_width + x]
+            })
+            .collect();
+        return Ok((target, res));
+    }
+
+    let values = to_linear(pixels, header.colorspace);
+    let horizontal = contributions(src_width, dst_width, filter);
+    let values = resample(&values, &horizontal, src_height, (src_width, 1));
+    let vertical = contributions(src_height, dst_height, filter);
+    // Column by column, the result is transposed back afterwards
+    let columns = resample(&values, &vertical, dst_width, (1, dst_width));
+    let values: Vec<[f32; 4]> = (0..dst_height)
+        .flat_map(|y| (0..dst_width).map(move |x| (x, y)))
+        .map(|(x, y)| columns[x * dst_height + y])
+        .collect();
+    Ok((target, from_linear(&values, header.colorspace)))
+}
@@ -0,0 +1,222 @@
+use crate::{decode::*, encode::*, *};
+use std::thread;
+
+/// Marks a serialized `ScanIndex`.
+pub const SCAN_INDEX_MAGIC: [u8; 4] = [b'q', b'o', b'i', b'x'];
+/// Size of a serialized `Checkpoint`.
+pub const CHECKPOINT_SIZE: usize = 8 + 8 + 4 + 4 * PREV_ARR_SIZE;
+
+/// The decoder's state right before the chunk at `offset` is decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Index of the first pixel, that is decoded from the chunk at `offset`.
+    pub pixel: usize,
+    /// Byte offset of the chunk in the encoded buffer.
+    pub offset: usize,
+    pub prev_pixel: Pixel,
+    pub prev_arr: [Pixel; PREV_ARR_SIZE],
+}
+
+/// Checkpoints into an encoded image, from which the image can be decoded in parallel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanIndex {
+    pub header: Header,
+    /// Size of the encoded buffer, that the index was created for.
+    pub encoded_size: usize,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+fn read_u64(iter: &mut BufIter<u8>) -> Result<usize, DecodeError> {
+    match iter.step_forward(8) {
+        None => Err(DecodeError::InvalidIndex),
+        Some(bytes) => Ok(u64::from_be_bytes(bytes.try_into().unwrap()) as usize),
+    }
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+impl ScanIndex {
+    /// Scans through the encoded `buffer` and records a checkpoint roughly every `interval` pixels.
+    /// Checkpoints can only be placed between chunks, so a run may push a checkpoint back a bit.
+    pub fn scan(buffer: &[u8], interval: usize) -> Result<Self, DecodeError> {
+        let header = decode_header(buffer)?;
+        let pixel_amount = header.pixel_amount();
+        let interval = interval.max(1);
+        let mut iter = BufIter::from(buffer, HEADER_SIZE..).unwrap();
+        let mut state = DecodeState::new();
+        // Every checkpoint needs at least one chunk, the header can claim far more pixels than there are
+        let capacity = (pixel_amount / interval + 1).min(buffer.len() - HEADER_SIZE);
+        let mut checkpoints = Vec::with_capacity(capacity);
+        let mut next = 0;
+
+        while state.decoded < pixel_amount {
+            if state.decoded >= next {
+                checkpoints.push(Checkpoint {
+                    pixel: state.decoded,
+                    offset: HEADER_SIZE + iter.idx(),
+                    prev_pixel: state.prev_pixel,
+                    prev_arr: state.prev_arr,
+                });
+                next = (state.decoded / interval + 1) * interval;
+            }
+            if let Some(bytes) = iter.look_forward(STREAM_END_SIZE) {
+                if bytes.starts_with(&STREAM_END) {
+                    break;
+                }
+            }
+            decode_chunk(&header, &mut iter, &mut state)?;
+        }
+
+        Ok(ScanIndex {
+            header,
+            encoded_size: buffer.len(),
+            checkpoints,
+        })
+    }
+
+    fn validate(&self, buffer: &[u8]) -> Result<(), DecodeError> {
+        let pixel_amount = self.header.pixel_amount();
+        let valid = decode_header(buffer)? == self.header
+            && buffer.len() == self.encoded_size
+            && self.checkpoints.first().map_or(pixel_amount == 0, |first| {
+                first.pixel == 0 && first.offset == HEADER_SIZE
+            })
+            && self
+                .checkpoints
+                .windows(2)
+                .all(|w| w[0].pixel < w[1].pixel && w[0].offset < w[1].offset)
+            && self
+                .checkpoints
+                .last()
+                .is_none_or(|last| last.pixel < pixel_amount && last.offset < buffer.len());
+        if valid {
+            Ok(())
+        } else {
+            Err(DecodeError::InvalidIndex)
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(4 + HEADER_SIZE + 8 + 4 + self.checkpoints.len() * CHECKPOINT_SIZE);
+        bytes.extend_from_slice(&SCAN_INDEX_MAGIC);
+        let mut header = [0; HEADER_SIZE];
+        encode_header(&self.header, &mut MutBufIter::new(&mut header)).unwrap();
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&(self.encoded_size as u64).to_be_bytes());
+        bytes.extend_from_slice(&(self.checkpoints.len() as u32).to_be_bytes());
+        for checkpoint in &self.checkpoints {
+            bytes.extend_from_slice(&(checkpoint.pixel as u64).to_be_bytes());
+            bytes.extend_from_slice(&(checkpoint.offset as u64).to_be_bytes());
+            for px in [checkpoint.prev_pixel]
+                .iter()
+                .chain(checkpoint.prev_arr.iter())
+            {
+                bytes.extend_from_slice(&[px.r, px.g, px.b, px.a]);
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut iter = BufIter::new(bytes);
+        if iter.step_forward(4) != Some(&SCAN_INDEX_MAGIC) {
+            return Err(DecodeError::InvalidIndex);
+        }
+        let header = decode_header(iter.step_forward(HEADER_SIZE).unwrap_or_default())?;
+        let encoded_size = read_u64(&mut iter)?;
+        let count = match iter.step_forward(4) {
+            None => Err(DecodeError::InvalidIndex),
+            Some(bytes) => Ok(u32::from_be_bytes(bytes.try_into().unwrap()) as usize),
+        }?;
+        if iter.len() - iter.idx() != count * CHECKPOINT_SIZE {
+            return Err(DecodeError::InvalidIndex);
+        }
+
+        let mut checkpoints = Vec::with_capacity(count);
+        for _ in 0..count {
+            let pixel = read_u64(&mut iter)?;
+            let offset = read_u64(&mut iter)?;
+            let prev_pixel = iter.step_forward(4).unwrap().into();
+            let mut prev_arr = [Pixel::zero(); PREV_ARR_SIZE];
+            for px in prev_arr.iter_mut() {
+                *px = iter.step_forward(4).unwrap().into();
+            }
+            checkpoints.push(Checkpoint {
+                pixel,
+                offset,
+                prev_pixel,
+                prev_arr,
+            });
+        }
+
+        Ok(ScanIndex {
+            header,
+            encoded_size,
+            checkpoints,
+        })
+    }
+
+    /// Decodes the `buffer` that this index was created for, using up to `threads` threads.
+    pub fn decode(&self, buffer: &[u8], threads: usize) -> Result<Vec<Pixel>, DecodeError> {
+        self.validate(buffer)?;
+        let header = &self.header;
+        let mut pixels = vec![Pixel::def(); header.pixel_amount()];
+        let count = self.checkpoints.len();
+        let threads = threads.clamp(1, count.max(1));
+
+        // Consecutive checkpoints are merged into one segment per thread
+        let mut starts: Vec<&Checkpoint> = (0..threads)
+            .map(|i| &self.checkpoints[i * count / threads])
+            .collect();
+        starts.dedup_by_key(|checkpoint| checkpoint.pixel);
+
+        thread::scope(|s| {
+            let mut handles = Vec::with_capacity(starts.len());
+            let mut rest = pixels.as_mut_slice();
+            for (i, &checkpoint) in starts.iter().enumerate() {
+                let (end_pixel, end_offset) = match starts.get(i + 1) {
+                    None => (header.pixel_amount(), buffer.len()),
+                    Some(next) => (next.pixel, next.offset),
+                };
+                let (segment, tail) = rest.split_at_mut(end_pixel - checkpoint.pixel);
+                rest = tail;
+                let is_last = i + 1 == starts.len();
+                handles.push(s.spawn(move || {
+                    let mut state = DecodeState {
+                        prev_arr: checkpoint.prev_arr,
+                        prev_pixel: checkpoint.prev_pixel,
+                        decoded: checkpoint.pixel,
+                    };
+                    let mut segment = MutBufIter::new(segment);
+                    decode_pixels(
+                        header,
+                        &mut BufIter::new(&buffer[checkpoint.offset..end_offset]),
+                        &mut segment,
+                        &mut state,
+                    )?;
+                    if !is_last && segment.idx() < segment.len() {
+                        return Err(DecodeError::InvalidIndex);
+                    }
+                    Ok(())
+                }));
+            }
+            handles
+                .into_iter()
+                .try_for_each(|handle| handle.join().unwrap())
+        })?;
+        Ok(pixels)
+    }
+}
+
+/// Decodes a standard QOI image in parallel, after scanning it for checkpoints.
+/// If the same image is decoded repeatedly, keep the `ScanIndex` around and use `ScanIndex::decode` instead.
+pub fn decode_parallel(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), DecodeError> {
+    let threads = default_threads();
+    let header = decode_header(buffer)?;
+    let index = ScanIndex::scan(buffer, header.pixel_amount() / threads)?;
+    let pixels = index.decode(buffer, threads)?;
+    Ok((header, pixels))
+}
@@ -0,0 +1,98 @@
+use crate::{decode::*, *};
+use std::collections::HashSet;
+
+/// Maximum length of a single `OP_RUN` chunk.
+pub const RUN_MAX: usize = 62;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpcodeStats {
+    pub chunks: usize,
+    pub bytes: usize,
+    pub pixels: usize,
+}
+
+/// Statistics of an encoded image, see `analyze`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub header: Header,
+    /// Size of the whole buffer, including header & end marker.
+    pub size: usize,
+    /// Per opcode in the order of `Opcode::ALL`.
+    pub opcodes: [OpcodeStats; 6],
+    /// Amount of `OP_RUN` chunks per run length, `run_lengths[0]` counts runs of 1.
+    pub run_lengths: [usize; RUN_MAX],
+    /// Chunks that encode a single new pixel, i.e. all but `OP_RUN`.
+    pub pixel_chunks: usize,
+    /// Pixels which missed the index, although they were stored in it earlier, \
+    /// but got replaced by a different pixel with the same hash.
+    pub collisions: usize,
+}
+
+impl Stats {
+    pub fn opcode(&self, opcode: Opcode) -> &OpcodeStats {
+        &self.opcodes[opcode as usize]
+    }
+
+    pub fn chunks(&self) -> usize {
+        self.opcodes.iter().map(|op| op.chunks).sum()
+    }
+
+    /// Share of the encoded chunk bytes, which belong to `opcode`.
+    pub fn byte_share(&self, opcode: Opcode) -> f64 {
+        let bytes: usize = self.opcodes.iter().map(|op| op.bytes).sum();
+        self.opcode(opcode).bytes as f64 / bytes.max(1) as f64
+    }
+
+    /// Share of the pixel chunks, which are `OP_INDEX`.
+    pub fn index_hit_rate(&self) -> f64 {
+        self.opcode(Opcode::INDEX).chunks as f64 / self.pixel_chunks.max(1) as f64
+    }
+
+    /// Share of the pixel chunks, which missed the index because of a hash collision.
+    pub fn collision_rate(&self) -> f64 {
+        self.collisions as f64 / self.pixel_chunks.max(1) as f64
+    }
+}
+
+/// Walks over the chunks of an encoded image & collects statistics about them. \
+/// Anything after the last pixel is ignored.
+pub fn analyze(buffer: &[u8]) -> Result<Stats, DecodeError> {
+    let header = decode_header(buffer)?;
+    let pixel_amount = header.pixel_amount();
+    let mut iter = BufIter::from(buffer, HEADER_SIZE..).unwrap();
+    let mut state = DecodeState::new();
+
+    let mut stats = Stats {
+        header,
+        size: buffer.len(),
+        opcodes: [OpcodeStats::default(); 6],
+        run_lengths: [0; RUN_MAX],
+        pixel_chunks: 0,
+        collisions: 0,
+    };
+    let mut seen = HashSet::new();
+    while state.decoded < pixel_amount {
+        let opcode = match iter.look_one() {
+            None => Opcode::RUN,
+            Some(&byte) => Opcode::from_byte(byte),
+        };
+        let (start, index) = (iter.idx(), state.prev_arr);
+        let count = decode_chunk(&header, &mut iter, &mut state)?;
+
+        let op = &mut stats.opcodes[opcode as usize];
+        op.chunks += 1;
+        op.bytes += iter.idx() - start;
+        op.pixels += count;
+        if opcode == Opcode::RUN {
+            stats.run_lengths[count - 1] += 1;
+            continue;
+        }
+        stats.pixel_chunks += 1;
+        let px = state.prev_pixel;
+        if opcode != Opcode::INDEX && index[px.pixel_hash()] != px && seen.contains(&px) {
+            stats.collisions += 1;
+        }
+        seen.insert(px);
+    }
+    Ok(stats)
+}
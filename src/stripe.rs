@@ -0,0 +1,197 @@
+use crate::{decode::*, encode::*, *};
+use std::{
+    sync::Mutex,
+    thread::{self, available_parallelism},
+};
+
+/// Marks the stripe table that is appended after the end marker of a striped image.
+pub const STRIPE_MAGIC: [u8; 4] = [b'q', b'o', b'i', b's'];
+/// Size of the stripe table's fixed part: rows per stripe, stripe count & magic.
+pub const STRIPE_TABLE_TAIL_SIZE: usize = 12;
+
+// A striped image is a regular QOI stream, where each stripe of rows starts without
+// depending on the previous stripes' pixels. The byte offset of each stripe is stored
+// after the end marker, where it is ignored by other decoders:
+//
+// [header] [stripe 0] ... [stripe n-1] [end marker] [offset: u64; n] [rows: u32] [n: u32] [b"qois"]
+//
+// Since the stripes still form a valid stream, striped images can be read by any QOI decoder.
+// An image with a single stripe is encoded exactly like `encode` does.
+
+fn rows_per_stripe(height: u32, stripes: usize) -> usize {
+    let stripes = stripes.clamp(1, height.max(1) as usize);
+    (height as usize).div_ceil(stripes).max(1)
+}
+
+// Runs `job` on all items with at most `available_parallelism()` threads, including the calling one,
+// which take the items from a shared queue. The results are in the order of the items.
+fn run_pool<T, R, F>(items: Vec<T>, job: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let amount = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(Vec::with_capacity(amount));
+    let work = || loop {
+        let next = queue.lock().unwrap().next();
+        match next {
+            Some((i, item)) => {
+                let res = job(item);
+                results.lock().unwrap().push((i, res));
+            }
+            None => break,
+        }
+    };
+    let threads = available_parallelism().map_or(1, |n| n.get()).min(amount);
+    thread::scope(|s| {
+        for _ in 1..threads {
+            // Without more threads, the remaining ones take over the work
+            if thread::Builder::new().spawn_scoped(s, work).is_err() {
+                break;
+            }
+        }
+        work();
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_unstable_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, res)| res).collect()
+}
+
+fn encode_stripe(
+    header: &Header,
+    pixels: &[Pixel],
+    is_first: bool,
+) -> Result<Vec<u8>, EncodeError> {
+    let mut buffer = vec![0; pixels.len() * (usize::from(ColorChannel::RGBA) + 1)];
+    let mut iter = MutBufIter::new(&mut buffer);
+    let mut state = if is_first {
+        EncodeState::new()
+    } else {
+        EncodeState::detached()
+    };
+    encode_chunks(header, pixels, &mut iter, &mut state)?;
+    let size = iter.idx();
+    buffer.truncate(size);
+    Ok(buffer)
+}
+
+/// Encodes the image in `stripes` horizontal stripes, which are encoded in parallel
+/// by at most `available_parallelism()` threads.
+/// The result can be read by `decode` as well as in parallel by `decode_striped`.
+pub fn encode_striped(
+    header: &Header,
+    pixels: &[Pixel],
+    stripes: usize,
+) -> Result<Vec<u8>, EncodeError> {
+    let pixel_amount = header.pixel_amount();
+    let pixels = match pixels.get(..pixel_amount) {
+        None => Err(EncodeError::MissingPixels {
+            expected_size: pixel_amount,
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+
+    let rows = rows_per_stripe(header.height, stripes);
+    let stripe_len = header.width as usize * rows;
+    if stripe_len == 0 || stripe_len >= pixel_amount {
+        return encode_allocated(header, pixels);
+    }
+
+    let stripes: Vec<_> = pixels.chunks(stripe_len).enumerate().collect();
+    let encoded = run_pool(stripes, |(i, stripe)| encode_stripe(header, stripe, i == 0));
+
+    let mut buffer = vec![0; HEADER_SIZE];
+    encode_header(header, &mut MutBufIter::new(&mut buffer))?;
+    let mut offsets = Vec::with_capacity(encoded.len());
+    for stripe in encoded {
+        offsets.push(buffer.len() as u64);
+        buffer.extend_from_slice(&stripe?);
+    }
+    buffer.extend_from_slice(&STREAM_END);
+    for offset in &offsets {
+        buffer.extend_from_slice(&offset.to_be_bytes());
+    }
+    buffer.extend_from_slice(&(rows as u32).to_be_bytes());
+    buffer.extend_from_slice(&(offsets.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(&STRIPE_MAGIC);
+    Ok(buffer)
+}
+
+struct StripeTable {
+    rows: usize,
+    // Byte ranges of all stripes
+    ranges: Vec<(usize, usize)>,
+}
+
+// Returns `None` if the buffer doesn't contain a stripe table
+fn read_stripe_table(header: &Header, buffer: &[u8]) -> Result<Option<StripeTable>, DecodeError> {
+    if buffer.len() < HEADER_SIZE + STREAM_END_SIZE + STRIPE_TABLE_TAIL_SIZE
+        || !buffer.ends_with(&STRIPE_MAGIC)
+    {
+        return Ok(None);
+    }
+    let tail = &buffer[buffer.len() - STRIPE_TABLE_TAIL_SIZE..];
+    let rows = u32::from_be_bytes(tail[0..4].try_into().unwrap()) as usize;
+    let count = u32::from_be_bytes(tail[4..8].try_into().unwrap()) as usize;
+
+    let table_size = count
+        .checked_mul(8)
+        .and_then(|size| size.checked_add(STRIPE_TABLE_TAIL_SIZE + STREAM_END_SIZE))
+        .ok_or(DecodeError::InvalidEncoding)?;
+    let stream_end = match buffer.len().checked_sub(table_size) {
+        Some(table_start) if table_start >= HEADER_SIZE => Ok(table_start),
+        _ => Err(DecodeError::InvalidEncoding),
+    }?;
+    let height = header.height as usize;
+    if count == 0 || rows == 0 || rows * (count - 1) >= height.max(1) || rows * count < height {
+        return Err(DecodeError::InvalidEncoding);
+    }
+
+    let offsets: Vec<usize> = buffer
+        [stream_end + STREAM_END_SIZE..buffer.len() - STRIPE_TABLE_TAIL_SIZE]
+        .chunks(8)
+        .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()) as usize)
+        .chain([stream_end])
+        .collect();
+    if offsets[0] != HEADER_SIZE || offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err(DecodeError::InvalidEncoding);
+    }
+
+    Ok(Some(StripeTable {
+        rows,
+        ranges: offsets.windows(2).map(|w| (w[0], w[1])).collect(),
+    }))
+}
+
+/// Decodes an image created by `encode_striped`, decoding all stripes in parallel.
+/// Images without a stripe table are decoded sequentially, just like `decode_allocated` does.
+pub fn decode_striped(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), DecodeError> {
+    let header = decode_header(buffer)?;
+    let table = match read_stripe_table(&header, buffer)? {
+        None => return decode_allocated(buffer),
+        Some(table) => table,
+    };
+
+    let mut pixels = vec![Pixel::def(); header.pixel_amount()];
+    let stripe_len = (header.width as usize).saturating_mul(table.rows).max(1);
+    let stripes: Vec<_> = pixels.chunks_mut(stripe_len).zip(table.ranges).collect();
+    run_pool(stripes, |(stripe, (start, end))| {
+        let mut stripe = MutBufIter::new(stripe);
+        decode_pixels(
+            &header,
+            &mut BufIter::new(&buffer[start..end]),
+            &mut stripe,
+            &mut DecodeState::new(),
+        )?;
+        if stripe.idx() < stripe.len() {
+            return Err(DecodeError::missing_pixels(&header, &stripe));
+        }
+        Ok(())
+    })
+    .into_iter()
+    .collect::<Result<(), DecodeError>>()?;
+    Ok((header, pixels))
+}
@@ -0,0 +1,336 @@
+use crate::*;
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+const TGA_HEADER_SIZE: usize = 18;
+const TGA_FOOTER: &[u8; 18] = b"TRUEVISION-XFILE.\0";
+
+// Image types
+const COLOR_MAPPED: u8 = 1;
+const TRUE_COLOR: u8 = 2;
+const GRAYSCALE: u8 = 3;
+const RLE: u8 = 8;
+
+// Image descriptor bits
+const RIGHT_TO_LEFT: u8 = 0x10;
+const TOP_TO_BOTTOM: u8 = 0x20;
+
+pub enum TgaError {
+    /// The data is shorter than the header, ID & color map.
+    MissingHeader,
+
+    /// The combination of image type, pixel depth & color map isn't supported.
+    UnsupportedFormat {
+        image_type: u8,
+        pixel_depth: u8,
+    },
+
+    InvalidDimensions(DimensionsError),
+
+    /// TGA stores the width & height in 16 bits, so neither can exceed 65535.
+    TooLarge {
+        width: u32,
+        height: u32,
+    },
+
+    /// A pixel refers to a color map entry that doesn't exist.
+    InvalidColorMapIndex(u16),
+
+    /// The pixel data is too short for the image's dimensions. \
+    /// When encoding, there are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+
+    IOError(std::io::Error),
+}
+
+impl fmt::Debug for TgaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingHeader => write!(f, "Missing Header: The data is too short."),
+            Self::UnsupportedFormat {
+                image_type,
+                pixel_depth,
+            } => {
+                write!(
+                    f,
+                    "Unsupported format: image type {} with a pixel depth of {}.",
+                    image_type, pixel_depth
+                )
+            }
+            Self::InvalidDimensions(err) => write!(f, "{:?}", err),
+            Self::TooLarge { width, height } => {
+                write!(
+                    f,
+                    "Too large: {}x{}. TGA images can be at most 65535 pixels wide & high.",
+                    width, height
+                )
+            }
+            Self::InvalidColorMapIndex(index) => {
+                write!(
+                    f,
+                    "Invalid color map index: {} is outside of the color map.",
+                    index
+                )
+            }
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+            Self::IOError(err) => write!(f, "IO Error: {}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for TgaError {
+    fn from(value: std::io::Error) -> Self {
+        TgaError::IOError(value)
+    }
+}
+
+impl From<DimensionsError> for TgaError {
+    fn from(value: DimensionsError) -> Self {
+        TgaError::InvalidDimensions(value)
+    }
+}
+
+use TgaError::*;
+
+// Converts a single stored pixel value of 8 (gray), 15/16, 24 or 32 bits
+fn read_color(bytes: &[u8]) -> Pixel {
+    match bytes.len() {
+        1 => Pixel::from([bytes[0]; 3]),
+        2 => {
+            let val = u16::from_le_bytes([bytes[0], bytes[1]]);
+            let scale = |bits: u16| ((bits & 0x1f) as u32 * 255 / 31) as u8;
+            Pixel::from([scale(val >> 10), scale(val >> 5), scale(val)])
+        }
+        3 => Pixel::from([bytes[2], bytes[1], bytes[0]]),
+        _ => Pixel::from([bytes[2], bytes[1], bytes[0], bytes[3]]),
+    }
+}
+
+/// Decodes a true color (15, 16, 24 or 32 bits), 8-bit grayscale or 8-bit color mapped TGA image, \
+/// with or without RLE compression, in any of the four orientations. \
+/// 32-bit images & color maps are returned as RGBA, others as RGB.
+pub fn decode(buffer: &[u8]) -> Result<(Header, Vec<Pixel>), TgaError> {
+    let head = buffer.get(..TGA_HEADER_SIZE).ok_or(MissingHeader)?;
+    let le16 = |i: usize| u16::from_le_bytes([head[i], head[i + 1]]);
+    let (id_length, color_map_type, image_type) = (head[0], head[1], head[2]);
+    let (map_first, map_length, map_depth) = (le16(3), le16(5), head[7]);
+    let (width, height) = (le16(12) as u32, le16(14) as u32);
+    let (pixel_depth, descriptor) = (head[16], head[17]);
+
+    let unsupported = UnsupportedFormat {
+        image_type,
+        pixel_depth,
+    };
+    let pixel_size = match (image_type & !RLE, pixel_depth, color_map_type) {
+        (COLOR_MAPPED, 8, 1) => 1,
+        (TRUE_COLOR, 15 | 16 | 24 | 32, _) => pixel_depth.div_ceil(8) as usize,
+        (GRAYSCALE, 8, _) => 1,
+        _ => return Err(unsupported),
+    };
+    let map_size = match map_depth {
+        0 if color_map_type == 0 => 0,
+        15 | 16 | 24 | 32 => map_depth.div_ceil(8) as usize,
+        _ => return Err(unsupported),
+    };
+
+    let header = Header::new(width, height, ColorChannel::RGB, ColorSpace::SRGB);
+    header.check_dimensions()?;
+
+    let mut pos = TGA_HEADER_SIZE + id_length as usize;
+    let map_end = pos + map_length as usize * map_size;
+    let color_map: Vec<Pixel> = match color_map_type {
+        0 => Vec::new(),
+        _ => buffer
+            .get(pos..map_end)
+            .ok_or(MissingHeader)?
+            .chunks(map_size)
+            .map(read_color)
+            .collect(),
+    };
+    pos = map_end;
+
+    // Collects the stored pixel values, RLE packets may cross scanlines
+    let amount = header.pixel_amount();
+    let missing = |received_size: usize| MissingPixels {
+        expected_size: amount,
+        received_size,
+    };
+    let raw = if image_type & RLE != 0 {
+        let mut raw = Vec::with_capacity(amount * pixel_size);
+        while raw.len() < amount * pixel_size {
+            let received = raw.len() / pixel_size;
+            let packet = *buffer.get(pos).ok_or(missing(received))?;
+            let count = (packet & 0x7f) as usize + 1;
+            pos += 1;
+            if packet & 0x80 != 0 {
+                let value = buffer.get(pos..pos + pixel_size).ok_or(missing(received))?;
+                for _ in 0..count {
+                    raw.extend_from_slice(value);
+                }
+                pos += pixel_size;
+            } else {
+                let values = buffer
+                    .get(pos..pos + count * pixel_size)
+                    .ok_or(missing(received))?;
+                raw.extend_from_slice(values);
+                pos += count * pixel_size;
+            }
+        }
+        raw.truncate(amount * pixel_size);
+        raw
+    } else {
+        let available = buffer.len().saturating_sub(pos) / pixel_size;
+        buffer
+            .get(pos..pos + amount * pixel_size)
+            .ok_or(missing(available))?
+            .to_vec()
+    };
+
+    let (width, height) = (width as usize, height as usize);
+    let mut pixels = vec![Pixel::def(); amount];
+    for (i, value) in raw.chunks(pixel_size).enumerate() {
+        let (mut x, mut y) = (i % width, i / width);
+        if descriptor & RIGHT_TO_LEFT != 0 {
+            x = width - 1 - x;
+        }
+        if descriptor & TOP_TO_BOTTOM == 0 {
+            y = height - 1 - y;
+        }
+        pixels[y * width + x] = match image_type & !RLE {
+            COLOR_MAPPED => {
+                let idx = value[0] as u16;
+                *idx.checked_sub(map_first)
+                    .and_then(|idx| color_map.get(idx as usize))
+                    .ok_or(InvalidColorMapIndex(idx))?
+            }
+            _ => read_color(value),
+        };
+    }
+
+    let has_alpha = match image_type & !RLE {
+        COLOR_MAPPED => map_depth == 32,
+        _ => pixel_depth == 32,
+    };
+    let channels = if has_alpha {
+        ColorChannel::RGBA
+    } else {
+        ColorChannel::RGB
+    };
+    Ok((Header { channels, ..header }, pixels))
+}
+
+// Appends the RLE packets of a single scanline
+fn encode_rle(line: &[Pixel], bytes_per_pixel: usize, out: &mut Vec<u8>) {
+    let value = |px: &Pixel| [px.b, px.g, px.r, px.a];
+    let mut i = 0;
+    while i < line.len() {
+        let run = line[i..]
+            .iter()
+            .take(128)
+            .take_while(|px| value(px)[..bytes_per_pixel] == value(&line[i])[..bytes_per_pixel])
+            .count();
+        if run > 1 {
+            out.push(0x80 | (run - 1) as u8);
+            out.extend_from_slice(&value(&line[i])[..bytes_per_pixel]);
+            i += run;
+            continue;
+        }
+        // Raw packets end where a run of at least 2 equal pixels starts
+        let start = i;
+        while i < line.len() && i - start < 128 {
+            if i + 1 < line.len()
+                && value(&line[i])[..bytes_per_pixel] == value(&line[i + 1])[..bytes_per_pixel]
+            {
+                break;
+            }
+            i += 1;
+        }
+        out.push((i - start - 1) as u8);
+        for px in &line[start..i] {
+            out.extend_from_slice(&value(px)[..bytes_per_pixel]);
+        }
+    }
+}
+
+/// Encodes a top-to-bottom true color TGA image with 24 bits per pixel for RGB & 32 bits for RGBA. \
+/// With `rle`, every scanline is compressed separately.
+pub fn encode(header: &Header, pixels: &[Pixel], rle: bool) -> Result<Vec<u8>, TgaError> {
+    header.check_dimensions()?;
+    if header.width > u16::MAX as u32 || header.height > u16::MAX as u32 {
+        return Err(TooLarge {
+            width: header.width,
+            height: header.height,
+        });
+    }
+    let pixels = match pixels.get(..header.pixel_amount()) {
+        None => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.len(),
+        }),
+        Some(pixels) => Ok(pixels),
+    }?;
+
+    let bytes_per_pixel = header.bytes_per_pixel();
+    let alpha_bits = match header.channels {
+        ColorChannel::RGB => 0,
+        ColorChannel::RGBA => 8,
+    };
+    let image_type = if rle { TRUE_COLOR | RLE } else { TRUE_COLOR };
+
+    let mut out = Vec::with_capacity(TGA_HEADER_SIZE + pixels.len() * bytes_per_pixel);
+    out.extend_from_slice(&[0, 0, image_type, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    out.extend_from_slice(&(header.width as u16).to_le_bytes());
+    out.extend_from_slice(&(header.height as u16).to_le_bytes());
+    out.extend_from_slice(&[8 * bytes_per_pixel as u8, alpha_bits | TOP_TO_BOTTOM]);
+    for line in pixels.chunks(header.width as usize) {
+        if rle {
+            encode_rle(line, bytes_per_pixel, &mut out);
+        } else {
+            for px in line {
+                out.extend_from_slice(&[px.b, px.g, px.r, px.a][..bytes_per_pixel]);
+            }
+        }
+    }
+    // TGA 2.0 footer without extension & developer area
+    out.extend_from_slice(&[0; 8]);
+    out.extend_from_slice(TGA_FOOTER);
+    Ok(out)
+}
+
+pub fn read<P>(filepath: P) -> Result<(Header, Vec<Pixel>), TgaError>
+where
+    P: AsRef<Path>,
+{
+    decode(&fs::read(filepath)?)
+}
+
+pub fn write<P>(
+    filepath: P,
+    header: &Header,
+    pixels: &[Pixel],
+    rle: bool,
+) -> Result<usize, TgaError>
+where
+    P: AsRef<Path>,
+{
+    let buffer = encode(header, pixels, rle)?;
+    let mut file: File = open_file_w(filepath)?;
+    file.write_all(&buffer)?;
+    Ok(buffer.len())
+}
@@ -0,0 +1,157 @@
+use crate::*;
+use std::fmt;
+
+/// Flips, rotations (clockwise) & the transposition, which mirrors along the main diagonal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    FlipHorizontal,
+    FlipVertical,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Transpose,
+}
+
+pub enum TransformError {
+    /// There are fewer pixels than `width * height`.
+    MissingPixels {
+        expected_size: usize,
+        received_size: usize,
+    },
+}
+
+impl fmt::Debug for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingPixels {
+                expected_size,
+                received_size,
+            } => {
+                write!(
+                    f,
+                    "Missing Pixels: Expected {} pixels, instead received {} pixels.",
+                    expected_size, received_size
+                )
+            }
+        }
+    }
+}
+
+use TransformError::*;
+
+fn checked_pixels<T>(header: &Header, pixels: T) -> Result<T, TransformError>
+where
+    T: AsRef<[Pixel]>,
+{
+    match pixels.as_ref().len() < header.pixel_amount() {
+        true => Err(MissingPixels {
+            expected_size: header.pixel_amount(),
+            received_size: pixels.as_ref().len(),
+        }),
+        false => Ok(pixels),
+    }
+}
+
+fn flip_horizontal(width: usize, pixels: &mut [Pixel]) {
+    pixels.chunks_exact_mut(width).for_each(|row| row.reverse());
+}
+
+fn flip_vertical(width: usize, pixels: &mut [Pixel]) {
+    let height = pixels.len() / width;
+    for y in 0..height / 2 {
+        let (top, bottom) = pixels.split_at_mut((height - 1 - y) * width);
+        top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+    }
+}
+
+// Follows the cycles of the permutation, the pixel at `i` moves to `i * height mod (n - 1)`
+fn transpose(width: usize, pixels: &mut [Pixel]) {
+    let n = pixels.len();
+    let height = n / width;
+    if width == 1 || height == 1 {
+        return;
+    }
+    let mut visited = vec![0u64; n.div_ceil(64)];
+    for start in 1..n - 1 {
+        if visited[start / 64] & (1 << (start % 64)) != 0 {
+            continue;
+        }
+        let (mut i, mut carried) = (start, pixels[start]);
+        loop {
+            i = i * height % (n - 1);
+            std::mem::swap(&mut pixels[i], &mut carried);
+            visited[i / 64] |= 1 << (i % 64);
+            if i == start {
+                break;
+            }
+        }
+    }
+}
+
+impl Transform {
+    /// Dimensions after the transform.
+    pub fn dimensions(&self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Transform::Rotate90 | Transform::Rotate270 | Transform::Transpose => (height, width),
+            _ => (width, height),
+        }
+    }
+
+    /// Transforms the pixels in place & updates the dimensions of `header`. \
+    /// Pixels after the first `width * height` are left as they are.
+    pub fn apply(&self, header: &mut Header, pixels: &mut [Pixel]) -> Result<(), TransformError> {
+        let pixels = &mut checked_pixels(header, pixels)?[..header.pixel_amount()];
+        let (width, height) = (header.width as usize, header.height as usize);
+        // Empty images have no pixels to move, only their dimensions change
+        if pixels.is_empty() {
+            (header.width, header.height) = self.dimensions(header.width, header.height);
+            return Ok(());
+        }
+        match self {
+            Transform::FlipHorizontal => flip_horizontal(width, pixels),
+            Transform::FlipVertical => flip_vertical(width, pixels),
+            Transform::Rotate90 => {
+                transpose(width, pixels);
+                flip_horizontal(height, pixels);
+            }
+            Transform::Rotate180 => pixels.reverse(),
+            Transform::Rotate270 => {
+                transpose(width, pixels);
+                flip_vertical(height, pixels);
+            }
+            Transform::Transpose => transpose(width, pixels),
+        }
+        (header.width, header.height) = self.dimensions(header.width, header.height);
+        Ok(())
+    }
+
+    /// Returns the transformed copy of the pixels & its header.
+    pub fn applied(
+        &self,
+        header: &Header,
+        pixels: &[Pixel],
+    ) -> Result<(Header, Vec<Pixel>), TransformError> {
+        let pixels = checked_pixels(header, pixels)?;
+        let (width, height) = (header.width as usize, header.height as usize);
+        let (new_width, new_height) = self.dimensions(header.width, header.height);
+        // Index of the source pixel for the destination pixel (x, y)
+        let src = |x: usize, y: usize| match self {
+            Transform::FlipHorizontal => y * width + width - 1 - x,
+            Transform::FlipVertical => (height - 1 - y) * width + x,
+            Transform::Rotate90 => (height - 1 - x) * width + y,
+            Transform::Rotate180 => (height - 1 - y) * width + width - 1 - x,
+            Transform::Rotate270 => x * width + width - 1 - y,
+            Transform::Transpose => x * width + y,
+        };
+        let res = (0..new_height as usize)
+            .flat_map(|y| (0..new_width as usize).map(move |x| (x, y)))
+            .map(|(x, y)| pixels[src(x, y)])
+            .collect();
+        let header = Header {
+            width: new_width,
+            height: new_height,
+            ..*header
+        };
+        Ok((header, res))
+    }
+}
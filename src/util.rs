@@ -1,6 +1,7 @@
 use crate::*;
+use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Pixel {
     pub r: u8,
     pub g: u8,
@@ -20,33 +21,34 @@ const fn hex_val(c: u8) -> u8 {
 
 impl Pixel {
     pub fn from_hex(hex: &str) -> Self {
-        match hex.len() {
-			3 => Pixel {
-				r: 17 * hex_val(hex.bytes().nth(0).unwrap()),
-				g: 17 * hex_val(hex.bytes().nth(1).unwrap()),
-				b: 17 * hex_val(hex.bytes().nth(2).unwrap()),
-				a: 255,
-			},
-			4 => Pixel {
-				r: 17 * hex_val(hex.bytes().nth(0).unwrap()),
-				g: 17 * hex_val(hex.bytes().nth(1).unwrap()),
-				b: 17 * hex_val(hex.bytes().nth(2).unwrap()),
-				a: 17 * hex_val(hex.bytes().nth(3).unwrap()),
-			},
-			6 => Pixel {
-				r: 16 * hex_val(hex.bytes().nth(0).unwrap()) + hex_val(hex.bytes().nth(1).unwrap()),
-				g: 16 * hex_val(hex.bytes().nth(2).unwrap()) + hex_val(hex.bytes().nth(3).unwrap()),
-				b: 16 * hex_val(hex.bytes().nth(4).unwrap()) + hex_val(hex.bytes().nth(5).unwrap()),
-				a: 255,
-			},
-			8 => Pixel {
-				r: 16 * hex_val(hex.bytes().nth(0).unwrap()) + hex_val(hex.bytes().nth(1).unwrap()),
-				g: 16 * hex_val(hex.bytes().nth(2).unwrap()) + hex_val(hex.bytes().nth(3).unwrap()),
-				b: 16 * hex_val(hex.bytes().nth(4).unwrap()) + hex_val(hex.bytes().nth(5).unwrap()),
-				a: 16 * hex_val(hex.bytes().nth(6).unwrap()) + hex_val(hex.bytes().nth(7).unwrap()),
-			},
-			_ => panic!("Unable to parse Pixel values from a hex-string that isn't 3,4,6 or 8 characters long"),
-		}
+        let h = hex.as_bytes();
+        match h.len() {
+            3 => Pixel {
+                r: 17 * hex_val(h[0]),
+                g: 17 * hex_val(h[1]),
+                b: 17 * hex_val(h[2]),
+                a: 255,
+            },
+            4 => Pixel {
+                r: 17 * hex_val(h[0]),
+                g: 17 * hex_val(h[1]),
+                b: 17 * hex_val(h[2]),
+                a: 17 * hex_val(h[3]),
+            },
+            6 => Pixel {
+                r: 16 * hex_val(h[0]) + hex_val(h[1]),
+                g: 16 * hex_val(h[2]) + hex_val(h[3]),
+                b: 16 * hex_val(h[4]) + hex_val(h[5]),
+                a: 255,
+            },
+            8 => Pixel {
+                r: 16 * hex_val(h[0]) + hex_val(h[1]),
+                g: 16 * hex_val(h[2]) + hex_val(h[3]),
+                b: 16 * hex_val(h[4]) + hex_val(h[5]),
+                a: 16 * hex_val(h[6]) + hex_val(h[7]),
+            },
+            _ => panic!("Unable to parse Pixel values from a hex-string that isn't 3,4,6 or 8 characters long"),
+        }
     }
 
     pub fn pixel_hash(&self) -> usize {
@@ -170,6 +172,8 @@ impl From<ColorChannel> for usize {
     }
 }
 
+/// How the r, g & b channels are encoded, alpha is always linear. \
+/// See `color::convert_colorspace` to convert between them.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ColorSpace {
     SRGB,
@@ -215,11 +219,27 @@ impl Header {
     }
 
     pub fn pixel_amount(&self) -> usize {
-        (self.width * self.height) as usize
+        (self.width as usize).saturating_mul(self.height as usize)
     }
 
     pub fn pixel_len(&self) -> usize {
-        self.pixel_amount() * self.bytes_per_pixel()
+        self.pixel_amount().saturating_mul(self.bytes_per_pixel())
+    }
+
+    /// Images need to have at least one and at most `PIXELS_MAX` pixels.
+    pub fn has_valid_dimensions(&self) -> bool {
+        self.width > 0 && self.height > 0 && self.pixel_amount() <= PIXELS_MAX
+    }
+
+    /// Like `has_valid_dimensions`, but returns the dimensions as error, if they aren't valid.
+    pub fn check_dimensions(&self) -> Result<(), DimensionsError> {
+        match self.has_valid_dimensions() {
+            true => Ok(()),
+            false => Err(DimensionsError {
+                width: self.width,
+                height: self.height,
+            }),
+        }
     }
 
     pub fn max_bytes_per_pixel(&self) -> usize {
@@ -231,8 +251,81 @@ impl Header {
     }
 
     pub fn max_size(&self) -> usize {
-        self.pixel_amount() * self.max_bytes_per_pixel() * (self.channels as usize + 1)
-            + HEADER_SIZE
-            + STREAM_END_SIZE
+        self.pixel_amount()
+            .saturating_mul(self.max_bytes_per_pixel() * (self.channels as usize + 1))
+            .saturating_add(HEADER_SIZE + STREAM_END_SIZE)
+    }
+}
+
+/// The image is empty or has more than `PIXELS_MAX` pixels.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DimensionsError {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl fmt::Debug for DimensionsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid dimensions: {}x{}. Images need to contain between 1 and {} pixels.",
+            self.width, self.height, PIXELS_MAX
+        )
+    }
+}
+
+/// The six chunk types of the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Opcode {
+    RGB,
+    RGBA,
+    INDEX,
+    DIFF,
+    LUMA,
+    RUN,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 6] = [
+        Opcode::RGB,
+        Opcode::RGBA,
+        Opcode::INDEX,
+        Opcode::DIFF,
+        Opcode::LUMA,
+        Opcode::RUN,
+    ];
+
+    /// Determines the chunk type from the first byte of a chunk.
+    pub fn from_byte(byte: u8) -> Self {
+        match (byte, byte & MASK_2) {
+            (OP_RGB, _) => Opcode::RGB,
+            (OP_RGBA, _) => Opcode::RGBA,
+            (_, OP_INDEX) => Opcode::INDEX,
+            (_, OP_DIFF) => Opcode::DIFF,
+            (_, OP_LUMA) => Opcode::LUMA,
+            _ => Opcode::RUN,
+        }
+    }
+
+    /// Size of the whole chunk in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Opcode::RGB => 4,
+            Opcode::RGBA => 5,
+            Opcode::LUMA => 2,
+            Opcode::INDEX | Opcode::DIFF | Opcode::RUN => 1,
+        }
+    }
+
+    /// Name as used by the specification, e.g. `QOI_OP_RGB`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Opcode::RGB => "QOI_OP_RGB",
+            Opcode::RGBA => "QOI_OP_RGBA",
+            Opcode::INDEX => "QOI_OP_INDEX",
+            Opcode::DIFF => "QOI_OP_DIFF",
+            Opcode::LUMA => "QOI_OP_LUMA",
+            Opcode::RUN => "QOI_OP_RUN",
+        }
     }
 }
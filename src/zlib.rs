@@ -0,0 +1,752 @@
+use std::fmt;
+
+// Minimal zlib (RFC 1950) & deflate (RFC 1951) implementation, as needed for PNG.
+// The decompressor follows the structure of zlib's `puff.c`.
+
+pub enum ZlibError {
+    /// The 2-byte zlib header is malformed or uses a preset dictionary.
+    InvalidHeader,
+
+    /// The data ended before the end of the last block.
+    UnexpectedEnd,
+
+    /// A stored block's length doesn't match its complement.
+    InvalidStoredLength,
+
+    /// Block type 3 is reserved.
+    InvalidBlockType,
+
+    /// The code lengths don't describe a valid Huffman code, \
+    /// or the data contains a code that isn't part of it.
+    InvalidCode,
+
+    /// A distance points before the start of the output.
+    InvalidDistance,
+
+    /// The decompressed data exceeds the limit passed to `inflate`.
+    /// The limit is returned.
+    OutputTooLarge(usize),
+
+    /// The Adler-32 checksum doesn't match the decompressed data.
+    /// The expected & calculated checksums are returned.
+    ChecksumMismatch { expected: u32, received: u32 },
+}
+
+impl fmt::Debug for ZlibError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "Invalid zlib header."),
+            Self::UnexpectedEnd => write!(f, "The compressed data ended unexpectedly."),
+            Self::InvalidStoredLength => {
+                write!(f, "A stored block's length doesn't match its complement.")
+            }
+            Self::InvalidBlockType => write!(f, "Invalid deflate block type 3."),
+            Self::InvalidCode => write!(f, "Invalid Huffman code in the compressed data."),
+            Self::InvalidDistance => {
+                write!(
+                    f,
+                    "A distance points before the start of the decompressed data."
+                )
+            }
+            Self::OutputTooLarge(limit) => {
+                write!(
+                    f,
+                    "Output too large: The decompressed data exceeds {} bytes.",
+                    limit
+                )
+            }
+            Self::ChecksumMismatch { expected, received } => {
+                write!(
+                    f,
+                    "Checksum mismatch: Expected {:#010x}, instead calculated {:#010x}.",
+                    expected, received
+                )
+            }
+        }
+    }
+}
+
+use ZlibError::*;
+
+const MAX_BITS: usize = 15;
+const MAX_LIT_CODES: usize = 286;
+const MAX_DIST_CODES: usize = 30;
+const FIXED_LIT_CODES: usize = 288;
+const FIXED_DIST_CODES: usize = 32;
+const WINDOW_SIZE: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// Order in which the code length code lengths are stored
+const CL_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest n, for which b can't overflow before the modulo
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, need: u32) -> Result<u32, ZlibError> {
+        let mut val = self.bit_buf;
+        while self.bit_count < need {
+            let byte = *self.data.get(self.pos).ok_or(UnexpectedEnd)?;
+            self.pos += 1;
+            val |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        self.bit_buf = if need == 32 { 0 } else { val >> need };
+        self.bit_count -= need;
+        Ok(if need == 32 {
+            val
+        } else {
+            val & ((1 << need) - 1)
+        })
+    }
+
+    fn align(&mut self) {
+        self.bit_buf = 0;
+        self.bit_count = 0;
+    }
+}
+
+// Canonical Huffman code, stored as the amount of codes per length & the symbols ordered by code
+struct Huffman {
+    count: [u16; MAX_BITS + 1],
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    // Incomplete codes are only allowed, if they consist of a single code
+    fn new(lengths: &[u8]) -> Result<Self, ZlibError> {
+        let mut count = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            count[len as usize] += 1;
+        }
+        let used = lengths.len() - count[0] as usize;
+
+        let mut left: i32 = 1;
+        for &amount in &count[1..] {
+            left <<= 1;
+            left -= amount as i32;
+            if left < 0 {
+                return Err(InvalidCode);
+            }
+        }
+        if left > 0 && used > 1 {
+            return Err(InvalidCode);
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + count[len];
+        }
+        let mut symbol = vec![0; used];
+        for (sym, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbol[offsets[len as usize] as usize] = sym as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Huffman { count, symbol })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ZlibError> {
+        // Codes are stored with their most significant bit first, so they are read bit by bit
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.count[len] as i32;
+            if code - count < first {
+                return Ok(self.symbol[(index + code - first) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(InvalidCode)
+    }
+}
+
+// The fixed distance code has 32 codes of length 5, of which the last 2 are invalid
+fn fixed_lengths() -> ([u8; FIXED_LIT_CODES], [u8; FIXED_DIST_CODES]) {
+    let mut lit = [8; FIXED_LIT_CODES];
+    lit[144..256].fill(9);
+    lit[256..280].fill(7);
+    (lit, [5; FIXED_DIST_CODES])
+}
+
+fn inflate_stored(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+) -> Result<(), ZlibError> {
+    reader.align();
+    let header = reader
+        .data
+        .get(reader.pos..reader.pos + 4)
+        .ok_or(UnexpectedEnd)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen {
+        return Err(InvalidStoredLength);
+    }
+    reader.pos += 4;
+    let data = reader
+        .data
+        .get(reader.pos..reader.pos + len as usize)
+        .ok_or(UnexpectedEnd)?;
+    if out.len() + data.len() > limit {
+        return Err(OutputTooLarge(limit));
+    }
+    out.extend_from_slice(data);
+    reader.pos += len as usize;
+    Ok(())
+}
+
+fn inflate_codes(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+    limit: usize,
+) -> Result<(), ZlibError> {
+    loop {
+        let sym = lit.decode(reader)? as usize;
+        match sym {
+            0..=255 if out.len() >= limit => return Err(OutputTooLarge(limit)),
+            0..=255 => out.push(sym as u8),
+            256 => return Ok(()),
+            _ => {
+                let sym = sym - 257;
+                if sym >= LENGTH_BASE.len() {
+                    return Err(InvalidCode);
+                }
+                let len =
+                    LENGTH_BASE[sym] as usize + reader.bits(LENGTH_EXTRA[sym] as u32)? as usize;
+                let sym = dist.decode(reader)? as usize;
+                if sym >= DIST_BASE.len() {
+                    return Err(InvalidCode);
+                }
+                let dist = DIST_BASE[sym] as usize + reader.bits(DIST_EXTRA[sym] as u32)? as usize;
+                if dist > out.len() {
+                    return Err(InvalidDistance);
+                }
+                if out.len() + len > limit {
+                    return Err(OutputTooLarge(limit));
+                }
+                let start = out.len() - dist;
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+fn inflate_dynamic(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+) -> Result<(), ZlibError> {
+    let nlen = reader.bits(5)? as usize + 257;
+    let ndist = reader.bits(5)? as usize + 1;
+    let ncode = reader.bits(4)? as usize + 4;
+    if nlen > MAX_LIT_CODES || ndist > MAX_DIST_CODES {
+        return Err(InvalidCode);
+    }
+
+    let mut lengths = [0u8; 19];
+    for &idx in &CL_ORDER[..ncode] {
+        lengths[idx] = reader.bits(3)? as u8;
+    }
+    let cl = Huffman::new(&lengths)?;
+
+    let mut lengths = vec![0u8; nlen + ndist];
+    let mut idx = 0;
+    while idx < nlen + ndist {
+        let sym = cl.decode(reader)?;
+        let (len, repeat) = match sym {
+            0..=15 => (sym as u8, 1),
+            16 => match idx.checked_sub(1) {
+                None => return Err(InvalidCode),
+                Some(prev) => (lengths[prev], 3 + reader.bits(2)? as usize),
+            },
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if idx + repeat > nlen + ndist {
+            return Err(InvalidCode);
+        }
+        lengths[idx..idx + repeat].fill(len);
+        idx += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(InvalidCode);
+    }
+
+    let lit = Huffman::new(&lengths[..nlen])?;
+    let dist = Huffman::new(&lengths[nlen..])?;
+    inflate_codes(reader, out, &lit, &dist, limit)
+}
+
+/// Decompresses raw deflate data & returns the amount of consumed bytes alongside the output. \
+/// Fails as soon as the output grows beyond `limit` bytes.
+pub fn inflate(data: &[u8], limit: usize) -> Result<(Vec<u8>, usize), ZlibError> {
+    let mut reader = BitReader {
+        data,
+        pos: 0,
+        bit_buf: 0,
+        bit_count: 0,
+    };
+    let mut out = Vec::with_capacity(data.len().saturating_mul(4).min(limit));
+    let (fixed_lit, fixed_dist) = fixed_lengths();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => inflate_stored(&mut reader, &mut out, limit)?,
+            1 => {
+                let lit = Huffman::new(&fixed_lit)?;
+                let dist = Huffman::new(&fixed_dist)?;
+                inflate_codes(&mut reader, &mut out, &lit, &dist, limit)?
+            }
+            2 => inflate_dynamic(&mut reader, &mut out, limit)?,
+            _ => return Err(InvalidBlockType),
+        }
+        if last {
+            return Ok((out, reader.pos));
+        }
+    }
+}
+
+/// Decompresses a zlib stream of at most `limit` bytes & verifies its checksum.
+pub fn decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, ZlibError> {
+    match data {
+        [cmf, flg, ..]
+            if cmf & 0x0f == 8
+                && cmf >> 4 <= 7
+                && flg & 0x20 == 0
+                && u16::from_be_bytes([*cmf, *flg]) % 31 == 0 => {}
+        _ => return Err(InvalidHeader),
+    }
+    let (out, used) = inflate(&data[2..], limit)?;
+    let checksum = data.get(2 + used..2 + used + 4).ok_or(UnexpectedEnd)?;
+    let expected = u32::from_be_bytes(checksum.try_into().unwrap());
+    let received = adler32(&out);
+    if expected != received {
+        return Err(ChecksumMismatch { expected, received });
+    }
+    Ok(out)
+}
+
+struct BitWriter {
+    out: Vec<u8>,
+    bit_buf: u64,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, val: u32, bits: u32) {
+        self.bit_buf |= (val as u64) << self.bit_count;
+        self.bit_count += bits;
+        while self.bit_count >= 8 {
+            self.out.push(self.bit_buf as u8);
+            self.bit_buf >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    // Huffman codes are stored with their most significant bit first
+    fn put_code(&mut self, code: u16, len: u8) {
+        let reversed = code.reverse_bits() >> (16 - len as u32);
+        self.put(reversed as u32, len as u32);
+    }
+
+    fn flush(&mut self) {
+        if self.bit_count > 0 {
+            self.out.push(self.bit_buf as u8);
+        }
+        self.bit_buf = 0;
+        self.bit_count = 0;
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match { len: u16, dist: u16 },
+}
+
+fn length_code(len: usize) -> usize {
+    LENGTH_BASE
+        .iter()
+        .rposition(|&base| base as usize <= len)
+        .unwrap()
+}
+
+fn dist_code(dist: usize) -> usize {
+    DIST_BASE
+        .iter()
+        .rposition(|&base| base as usize <= dist)
+        .unwrap()
+}
+
+// Huffman code lengths for the given frequencies, limited to `limit` bits
+fn huffman_lengths(freqs: &[u32], limit: usize) -> Vec<u8> {
+    let mut lengths = vec![0u8; freqs.len()];
+    let mut used: Vec<usize> = (0..freqs.len()).filter(|&i| freqs[i] > 0).collect();
+    match used.len() {
+        0 => return lengths,
+        1 => {
+            lengths[used[0]] = 1;
+            return lengths;
+        }
+        _ => {}
+    }
+
+    // Build the tree with two queues: sorted leaves & created nodes, which are created in order
+    used.sort_by_key(|&i| (freqs[i], i));
+    let leaves: Vec<u64> = used.iter().map(|&i| freqs[i] as u64).collect();
+    let mut nodes: Vec<(u64, usize, usize)> = Vec::with_capacity(used.len());
+    // Children are encoded as leaf indices, or as `leaves.len() + node index`
+    let (mut li, mut ni) = (0, 0);
+    let mut pick = |nodes: &Vec<(u64, usize, usize)>| -> usize {
+        if li < leaves.len() && (ni >= nodes.len() || leaves[li] <= nodes[ni].0) {
+            li += 1;
+            li - 1
+        } else {
+            ni += 1;
+            leaves.len() + ni - 1
+        }
+    };
+    let weight = |nodes: &Vec<(u64, usize, usize)>, idx: usize| {
+        if idx < leaves.len() {
+            leaves[idx]
+        } else {
+            nodes[idx - leaves.len()].0
+        }
+    };
+    for _ in 0..used.len() - 1 {
+        let a = pick(&nodes);
+        let b = pick(&nodes);
+        nodes.push((weight(&nodes, a) + weight(&nodes, b), a, b));
+    }
+
+    // Depths of all leaves, starting from the root, which is the last node
+    let mut depths = vec![0usize; leaves.len()];
+    let mut node_depth = vec![0usize; nodes.len()];
+    for n in (0..nodes.len()).rev() {
+        let (_, a, b) = nodes[n];
+        for child in [a, b] {
+            if child < leaves.len() {
+                depths[child] = node_depth[n] + 1;
+            } else {
+                node_depth[child - leaves.len()] = node_depth[n] + 1;
+            }
+        }
+    }
+
+    // Limit the lengths as described in Annex K.3 of the JPEG spec
+    let max = *depths.iter().max().unwrap();
+    let mut bl_count = vec![0usize; max.max(limit) + 1];
+    for &depth in &depths {
+        bl_count[depth] += 1;
+    }
+    for i in (limit + 1..=max).rev() {
+        while bl_count[i] > 0 {
+            let mut j = i - 2;
+            while bl_count[j] == 0 {
+                j -= 1;
+            }
+            bl_count[i] -= 2;
+            bl_count[i - 1] += 1;
+            bl_count[j + 1] += 2;
+            bl_count[j] -= 1;
+        }
+    }
+
+    // Hand out the lengths, shortest codes to the most frequent symbols
+    let mut len = 1;
+    for &sym in used.iter().rev() {
+        while bl_count[len] == 0 {
+            len += 1;
+        }
+        lengths[sym] = len as u8;
+        bl_count[len] -= 1;
+    }
+    lengths
+}
+
+fn canonical_codes(lengths: &[u8]) -> Vec<u16> {
+    let mut bl_count = [0u16; MAX_BITS + 1];
+    for &len in lengths {
+        bl_count[len as usize] += 1;
+    }
+    bl_count[0] = 0;
+    let mut next = [0u16; MAX_BITS + 1];
+    let mut code = 0;
+    for bits in 1..=MAX_BITS {
+        code = (code + bl_count[bits - 1]) << 1;
+        next[bits] = code;
+    }
+    lengths
+        .iter()
+        .map(|&len| {
+            if len == 0 {
+                0
+            } else {
+                next[len as usize] += 1;
+                next[len as usize] - 1
+            }
+        })
+        .collect()
+}
+
+// Run-length encodes the code lengths with the symbols 16, 17 & 18
+fn rle_lengths(lengths: &[u8]) -> Vec<(u8, u8)> {
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < lengths.len() {
+        let len = lengths[i];
+        let run = lengths[i..].iter().take_while(|&&l| l == len).count();
+        if len == 0 && run >= 3 {
+            let run = run.min(138);
+            if run <= 10 {
+                res.push((17, run as u8 - 3));
+            } else {
+                res.push((18, run as u8 - 11));
+            }
+            i += run;
+        } else if len != 0 && run >= 4 {
+            res.push((len, 0));
+            let run = (run - 1).min(6);
+            res.push((16, run as u8 - 3));
+            i += run + 1;
+        } else {
+            res.push((len, 0));
+            i += 1;
+        }
+    }
+    res
+}
+
+// A code with a single symbol is incomplete, which zlib only accepts for distances,
+// and an empty distance code isn't accepted by all decoders
+fn ensure_two_codes(freqs: &mut [u32]) {
+    let mut used = freqs.iter().filter(|&&freq| freq > 0).count();
+    for freq in freqs.iter_mut() {
+        if used >= 2 {
+            break;
+        }
+        if *freq == 0 {
+            *freq = 1;
+            used += 1;
+        }
+    }
+}
+
+fn write_block(writer: &mut BitWriter, tokens: &[Token], last: bool) {
+    let mut lit_freqs = [0u32; MAX_LIT_CODES];
+    let mut dist_freqs = [0u32; MAX_DIST_CODES];
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => lit_freqs[byte as usize] += 1,
+            Token::Match { len, dist } => {
+                lit_freqs[257 + length_code(len as usize)] += 1;
+                dist_freqs[dist_code(dist as usize)] += 1;
+            }
+        }
+    }
+    lit_freqs[256] = 1;
+    ensure_two_codes(&mut lit_freqs);
+    ensure_two_codes(&mut dist_freqs);
+
+    let lit_lengths = huffman_lengths(&lit_freqs, MAX_BITS);
+    let dist_lengths = huffman_lengths(&dist_freqs, MAX_BITS);
+    let nlen = 257.max(lit_lengths.iter().rposition(|&len| len > 0).unwrap() + 1);
+    let ndist = 1.max(dist_lengths.iter().rposition(|&len| len > 0).unwrap_or(0) + 1);
+
+    let mut all_lengths = lit_lengths[..nlen].to_vec();
+    all_lengths.extend_from_slice(&dist_lengths[..ndist]);
+    let rle = rle_lengths(&all_lengths);
+    let mut cl_freqs = [0u32; 19];
+    for &(sym, _) in &rle {
+        cl_freqs[sym as usize] += 1;
+    }
+    ensure_two_codes(&mut cl_freqs);
+    let cl_lengths = huffman_lengths(&cl_freqs, 7);
+    let cl_codes = canonical_codes(&cl_lengths);
+    let ncode = 4.max(CL_ORDER.iter().rposition(|&i| cl_lengths[i] > 0).unwrap() + 1);
+
+    writer.put(last as u32, 1);
+    writer.put(2, 2);
+    writer.put(nlen as u32 - 257, 5);
+    writer.put(ndist as u32 - 1, 5);
+    writer.put(ncode as u32 - 4, 4);
+    for &i in &CL_ORDER[..ncode] {
+        writer.put(cl_lengths[i] as u32, 3);
+    }
+    for &(sym, extra) in &rle {
+        writer.put_code(cl_codes[sym as usize], cl_lengths[sym as usize]);
+        match sym {
+            16 => writer.put(extra as u32, 2),
+            17 => writer.put(extra as u32, 3),
+            18 => writer.put(extra as u32, 7),
+            _ => {}
+        }
+    }
+
+    let lit_codes = canonical_codes(&lit_lengths);
+    let dist_codes = canonical_codes(&dist_lengths);
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => {
+                writer.put_code(lit_codes[byte as usize], lit_lengths[byte as usize])
+            }
+            Token::Match { len, dist } => {
+                let code = length_code(len as usize);
+                writer.put_code(lit_codes[257 + code], lit_lengths[257 + code]);
+                writer.put(
+                    len as u32 - LENGTH_BASE[code] as u32,
+                    LENGTH_EXTRA[code] as u32,
+                );
+                let code = dist_code(dist as usize);
+                writer.put_code(dist_codes[code], dist_lengths[code]);
+                writer.put(
+                    dist as u32 - DIST_BASE[code] as u32,
+                    DIST_EXTRA[code] as u32,
+                );
+            }
+        }
+    }
+    writer.put_code(lit_codes[256], lit_lengths[256]);
+}
+
+const HASH_BITS: u32 = 15;
+const MAX_CHAIN: usize = 64;
+const BLOCK_TOKENS: usize = 1 << 16;
+
+fn hash(data: &[u8]) -> usize {
+    let val = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+    (val.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+fn insert(data: &[u8], pos: usize, head: &mut [usize], prev: &mut [usize]) {
+    if pos + MIN_MATCH <= data.len() {
+        let h = hash(&data[pos..]);
+        prev[pos % WINDOW_SIZE] = head[h];
+        head[h] = pos;
+    }
+}
+
+/// Compresses `data` into raw deflate blocks, using greedy LZ77 matching & dynamic Huffman codes.
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        out: Vec::with_capacity(data.len() / 2),
+        bit_buf: 0,
+        bit_count: 0,
+    };
+    // Most recent position for each hash & the previous position with the same hash
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+    let mut tokens = Vec::with_capacity(BLOCK_TOKENS);
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let (mut best_len, mut best_dist) = (0, 0);
+        if pos + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - pos);
+            let mut candidate = head[hash(&data[pos..])];
+            let mut chain = 0;
+            while candidate != usize::MAX && pos - candidate < WINDOW_SIZE && chain < MAX_CHAIN {
+                let len = data[candidate..candidate + max_len]
+                    .iter()
+                    .zip(&data[pos..pos + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    best_dist = pos - candidate;
+                    if len == max_len {
+                        break;
+                    }
+                }
+                let next = prev[candidate % WINDOW_SIZE];
+                // Older positions may have been overwritten by newer ones
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            tokens.push(Token::Match {
+                len: best_len as u16,
+                dist: best_dist as u16,
+            });
+            for i in pos..pos + best_len {
+                insert(data, i, &mut head, &mut prev);
+            }
+            pos += best_len;
+        } else {
+            tokens.push(Token::Literal(data[pos]));
+            insert(data, pos, &mut head, &mut prev);
+            pos += 1;
+        }
+
+        if tokens.len() == BLOCK_TOKENS {
+            write_block(&mut writer, &tokens, false);
+            tokens.clear();
+        }
+    }
+    write_block(&mut writer, &tokens, true);
+    writer.flush();
+    writer.out
+}
+
+/// Compresses `data` into a zlib stream.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window, default compression level
+    let mut out = vec![0x78, 0x9c];
+    out.extend(deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
@@ -0,0 +1,30 @@
+// Fixtures shared by the integration tests, not every test uses all of them
+#![allow(dead_code)]
+
+use qoi::{Header, Pixel};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub fn qoi_paths() -> Vec<PathBuf> {
+    fs::read_dir(Path::new("./imgs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "qoi"))
+        .collect()
+}
+
+pub fn qoi_files() -> Vec<Vec<u8>> {
+    qoi_paths()
+        .into_iter()
+        .map(|path| fs::read(path).unwrap())
+        .collect()
+}
+
+pub fn qoi_imgs() -> Vec<(Header, Vec<Pixel>)> {
+    qoi_paths()
+        .into_iter()
+        .map(|path| qoi::read(path).unwrap())
+        .collect()
+}
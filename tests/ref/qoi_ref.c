@@ -0,0 +1,80 @@
+// Driver around the reference implementation in ref_qoi/qoi.h, used by tests/test_reference.rs.
+//
+// Usage: qoi_ref encode <width> <height> <channels> <colorspace> <in.raw> <out.qoi>
+//        qoi_ref decode <in.qoi> <out.raw>
+//
+// Raw files contain tightly packed pixels with <channels> bytes each.
+// Decoding always produces 4 channels.
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define QOI_IMPLEMENTATION
+#include "qoi.h"
+
+static void *read_file(const char *path, int *size) {
+	FILE *f = fopen(path, "rb");
+	if (!f) {
+		return NULL;
+	}
+	fseek(f, 0, SEEK_END);
+	*size = ftell(f);
+	fseek(f, 0, SEEK_SET);
+	void *data = malloc(*size > 0 ? *size : 1);
+	if (!data || fread(data, 1, *size, f) != (size_t)*size) {
+		fclose(f);
+		free(data);
+		return NULL;
+	}
+	fclose(f);
+	return data;
+}
+
+static int write_file(const char *path, const void *data, int size) {
+	FILE *f = fopen(path, "wb");
+	if (!f) {
+		return 0;
+	}
+	int ok = fwrite(data, 1, size, f) == (size_t)size;
+	fclose(f);
+	return ok;
+}
+
+int main(int argc, char **argv) {
+	if (argc == 8 && strcmp(argv[1], "encode") == 0) {
+		qoi_desc desc = {
+			.width = atoi(argv[2]),
+			.height = atoi(argv[3]),
+			.channels = atoi(argv[4]),
+			.colorspace = atoi(argv[5]),
+		};
+		int size;
+		void *raw = read_file(argv[6], &size);
+		if (!raw || size != (int)(desc.width * desc.height * desc.channels)) {
+			return 2;
+		}
+		int out_len;
+		void *encoded = qoi_encode(raw, &desc, &out_len);
+		if (!encoded || !write_file(argv[7], encoded, out_len)) {
+			return 3;
+		}
+		return 0;
+	}
+	if (argc == 4 && strcmp(argv[1], "decode") == 0) {
+		int size;
+		void *encoded = read_file(argv[2], &size);
+		if (!encoded) {
+			return 2;
+		}
+		qoi_desc desc;
+		void *raw = qoi_decode(encoded, size, &desc, 4);
+		if (!raw || !write_file(argv[3], raw, desc.width * desc.height * 4)) {
+			return 3;
+		}
+		return 0;
+	}
+	fprintf(stderr, "Usage: qoi_ref encode <width> <height> <channels> <colorspace> <in.raw> <out.qoi>\n");
+	fprintf(stderr, "       qoi_ref decode <in.qoi> <out.raw>\n");
+	return 1;
+}
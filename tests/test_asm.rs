@@ -0,0 +1,123 @@
+use qoi::{asm::*, decode, disasm::*, Pixel};
+use std::fs;
+
+// Formats a chunk the way `assemble` expects it
+fn to_asm(chunk: &Chunk) -> String {
+    match *chunk {
+        Chunk::RGB([r, g, b]) => format!("RGB {} {} {}", r, g, b),
+        Chunk::RGBA([r, g, b, a]) => format!("RGBA {} {} {} {}", r, g, b, a),
+        Chunk::INDEX(index) => format!("INDEX {}", index),
+        Chunk::DIFF { dr, dg, db } => format!("DIFF {} {} {}", dr, dg, db),
+        Chunk::LUMA { dg, dr_dg, db_dg } => format!("LUMA {} {} {}", dg, dr_dg, db_dg),
+        Chunk::RUN(length) => format!("RUN {}", length),
+    }
+}
+
+#[test]
+fn test_assemble() {
+    let text = "
+        # 4x2 RGBA
+        HEADER 4 2 4 0
+        RGBA 255 0 0 255
+        diff -1 0 1      # 254 0 1
+        QOI_OP_LUMA -3 1 2
+        RGB 10 20 30
+        INDEX 50         # hash of 255 0 0 255
+        RUN 3
+    ";
+    let buffer = assemble(text).unwrap();
+    decode::validate(&buffer).unwrap();
+    let (header, pixels) = decode::decode_allocated(&buffer).unwrap();
+    assert_eq!((header.width, header.height), (4, 2));
+    assert_eq!(
+        pixels,
+        [
+            Pixel::from([255, 0, 0, 255]),
+            Pixel::from([254, 0, 1, 255]),
+            Pixel::from([252, 253, 0, 255]),
+            Pixel::from([10, 20, 30, 255]),
+            Pixel::from([255, 0, 0, 255]),
+            Pixel::from([255, 0, 0, 255]),
+            Pixel::from([255, 0, 0, 255]),
+            Pixel::from([255, 0, 0, 255]),
+        ]
+    );
+}
+
+#[test]
+fn test_assemble_roundtrip() {
+    let buffer = fs::read("./imgs/testcard.qoi").unwrap();
+    let res = disassemble(&buffer).unwrap();
+    let header = res.header;
+    let mut text = format!(
+        "HEADER {} {} {} {}\n",
+        header.width,
+        header.height,
+        u8::from(header.channels),
+        u8::from(header.colorspace)
+    );
+    for line in &res.lines {
+        text.push_str(&to_asm(&line.chunk));
+        text.push('\n');
+    }
+    assert_eq!(assemble(&text).unwrap(), buffer);
+}
+
+#[test]
+fn test_assemble_edge_cases() {
+    // A leading run repeats the initial previous pixel
+    let buffer = assemble("HEADER 2 1 4 0\nRUN 2").unwrap();
+    let (_, pixels) = decode::decode_allocated(&buffer).unwrap();
+    assert_eq!(pixels, [Pixel::def(); 2]);
+
+    // The index starts out zeroed
+    let buffer = assemble("HEADER 1 1 4 0\nINDEX 0").unwrap();
+    let (_, pixels) = decode::decode_allocated(&buffer).unwrap();
+    assert_eq!(pixels, [Pixel::from([0, 0, 0, 0])]);
+
+    // Maximum length runs
+    let buffer = assemble("HEADER 125 1 3 0\nRGB 1 2 3\nRUN 62\nRUN 62").unwrap();
+    decode::validate(&buffer).unwrap();
+    let (_, pixels) = decode::decode_allocated(&buffer).unwrap();
+    assert_eq!(pixels, vec![Pixel::from([1, 2, 3]); 125]);
+
+    // The last run overflows the image
+    let buffer = assemble("HEADER 10 1 3 0\nRGB 1 2 3\nRUN 62").unwrap();
+    assert!(matches!(
+        decode::validate(&buffer),
+        Err(decode::DecodeError::RunOverflow { .. })
+    ));
+}
+
+#[test]
+fn test_assemble_errors() {
+    assert!(matches!(assemble(""), Err(AsmError::MissingHeader)));
+    assert!(matches!(
+        assemble("RGB 1 2 3"),
+        Err(AsmError::MissingHeader)
+    ));
+    assert!(matches!(
+        assemble("HEADER 1 1 4 0\n\nFOO 1"),
+        Err(AsmError::UnknownMnemonic { line: 3, .. })
+    ));
+    assert!(matches!(
+        assemble("HEADER 1 1 4 0\nRGB 1 2"),
+        Err(AsmError::InvalidArguments {
+            line: 2,
+            expected: 3
+        })
+    ));
+    assert!(matches!(
+        assemble("HEADER 1 1 4 0\nDIFF 0 2 0"),
+        Err(AsmError::ArgumentOutOfRange {
+            value: 2,
+            min: -2,
+            max: 1,
+            ..
+        })
+    ));
+    assert!(matches!(
+        assemble("HEADER 1 1 4 0\nRUN 63"),
+        Err(AsmError::ArgumentOutOfRange { .. })
+    ));
+}
@@ -0,0 +1,174 @@
+mod common;
+
+use qoi::{bmp::*, ColorChannel, Pixel};
+
+fn info_header(width: i32, height: i32, bits: u16, compression: u32, colors: u32) -> Vec<u8> {
+    let mut dib = 40u32.to_le_bytes().to_vec();
+    dib.extend_from_slice(&width.to_le_bytes());
+    dib.extend_from_slice(&height.to_le_bytes());
+    dib.extend_from_slice(&1u16.to_le_bytes());
+    dib.extend_from_slice(&bits.to_le_bytes());
+    dib.extend_from_slice(&compression.to_le_bytes());
+    dib.extend_from_slice(&[0; 12]);
+    dib.extend_from_slice(&colors.to_le_bytes());
+    dib.extend_from_slice(&[0; 4]);
+    dib
+}
+
+fn build_bmp(dib: &[u8], palette: &[u8], data: &[u8]) -> Vec<u8> {
+    let offset = 14 + dib.len() + palette.len();
+    let mut out = b"BM".to_vec();
+    out.extend_from_slice(&((offset + data.len()) as u32).to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(offset as u32).to_le_bytes());
+    out.extend_from_slice(dib);
+    out.extend_from_slice(palette);
+    out.extend_from_slice(data);
+    out
+}
+
+const RED: [u8; 3] = [255, 0, 0];
+const GREEN: [u8; 3] = [0, 255, 0];
+const BLUE: [u8; 3] = [0, 0, 255];
+const WHITE: [u8; 3] = [255, 255, 255];
+// Palette entries are stored as BGR0
+const PALETTE: [u8; 16] = [0, 0, 255, 0, 0, 255, 0, 0, 255, 0, 0, 0, 255, 255, 255, 0];
+
+#[test]
+fn test_roundtrip() {
+    for (header, pixels) in common::qoi_imgs() {
+        let encoded = encode(&header, &pixels).unwrap();
+        let (res_header, res_pixels) = decode(&encoded).unwrap();
+        assert_eq!(res_header, header);
+        assert_eq!(res_pixels, pixels);
+    }
+}
+
+#[test]
+fn test_24_bit_orientation() {
+    // 2x2, rows are padded to 8 bytes
+    let data = [0, 0, 255, 0, 255, 0, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0];
+    let expected_rows = [[RED, GREEN], [BLUE, WHITE]];
+
+    let (header, pixels) = decode(&build_bmp(&info_header(2, -2, 24, 0, 0), &[], &data)).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    let expected: Vec<Pixel> = expected_rows
+        .concat()
+        .into_iter()
+        .map(Pixel::from)
+        .collect();
+    assert_eq!(pixels, expected);
+
+    let (_, pixels) = decode(&build_bmp(&info_header(2, 2, 24, 0, 0), &[], &data)).unwrap();
+    let expected: Vec<Pixel> = [expected_rows[1], expected_rows[0]]
+        .concat()
+        .into_iter()
+        .map(Pixel::from)
+        .collect();
+    assert_eq!(pixels, expected);
+
+    // BITMAPCOREHEADER with 16-bit dimensions
+    let mut core = 12u32.to_le_bytes().to_vec();
+    core.extend_from_slice(&[2, 0, 2, 0, 1, 0, 24, 0]);
+    let (_, pixels) = decode(&build_bmp(&core, &[], &data)).unwrap();
+    assert_eq!(pixels, expected);
+}
+
+#[test]
+fn test_32_and_16_bit() {
+    // The fourth byte is unused, if it's 0 everywhere
+    let data = [0, 0, 255, 0, 255, 0, 0, 0];
+    let (header, pixels) = decode(&build_bmp(&info_header(2, 1, 32, 0, 0), &[], &data)).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    assert_eq!(pixels, vec![Pixel::from(RED), Pixel::from(BLUE)]);
+
+    let data = [0, 0, 255, 128, 255, 0, 0, 0];
+    let (header, pixels) = decode(&build_bmp(&info_header(2, 1, 32, 0, 0), &[], &data)).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGBA);
+    assert_eq!(
+        pixels,
+        vec![Pixel::from([255, 0, 0, 128]), Pixel::from([0, 0, 255, 0])]
+    );
+
+    // 5-5-5 by default, 5-6-5 with bit fields
+    let data = [0x00, 0x7c, 0xff, 0x03];
+    let (_, pixels) = decode(&build_bmp(&info_header(2, 1, 16, 0, 0), &[], &data)).unwrap();
+    assert_eq!(pixels, vec![Pixel::from(RED), Pixel::from([0, 255, 255])]);
+
+    let mut dib = info_header(2, 1, 16, 3, 0);
+    for mask in [0xf800u32, 0x07e0, 0x001f] {
+        dib.extend_from_slice(&mask.to_le_bytes());
+    }
+    let data = [0xe0, 0x07, 0x1f, 0x00];
+    let (_, pixels) = decode(&build_bmp(&dib, &[], &data)).unwrap();
+    assert_eq!(pixels, vec![Pixel::from(GREEN), Pixel::from(BLUE)]);
+}
+
+#[test]
+fn test_palette() {
+    // 3x2 with 4 bits per pixel, bottom-up
+    let data = [0x01, 0x20, 0, 0, 0x33, 0x30, 0, 0];
+    let bmp = build_bmp(&info_header(3, 2, 4, 0, 4), &PALETTE, &data);
+    let (header, pixels) = decode(&bmp).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    let expected: Vec<Pixel> = [WHITE, WHITE, WHITE, RED, GREEN, BLUE]
+        .into_iter()
+        .map(Pixel::from)
+        .collect();
+    assert_eq!(pixels, expected);
+
+    let data = [0x40, 0, 0, 0];
+    let bmp = build_bmp(&info_header(1, 1, 8, 0, 4), &PALETTE, &data);
+    assert!(matches!(
+        decode(&bmp),
+        Err(BmpError::InvalidPaletteIndex(64))
+    ));
+}
+
+#[test]
+fn test_rle() {
+    // RLE8, 4x2: a run of 4 in the bottom row, an absolute run of 3 & a run of 1 in the top row
+    let data = [4, 1, 0, 0, 0, 3, 0, 1, 2, 0, 1, 3, 0, 1];
+    let bmp = build_bmp(&info_header(4, 2, 8, 1, 4), &PALETTE, &data);
+    let (_, pixels) = decode(&bmp).unwrap();
+    let expected: Vec<Pixel> = [RED, GREEN, BLUE, WHITE, GREEN, GREEN, GREEN, GREEN]
+        .into_iter()
+        .map(Pixel::from)
+        .collect();
+    assert_eq!(pixels, expected);
+
+    // RLE4, 5x1: a run of alternating indices & a delta, which skips the last pixel
+    let data = [4, 0x31, 0, 2, 1, 0, 0, 1];
+    let bmp = build_bmp(&info_header(5, 1, 4, 2, 4), &PALETTE, &data);
+    let (_, pixels) = decode(&bmp).unwrap();
+    let expected: Vec<Pixel> = [WHITE, GREEN, WHITE, GREEN, RED]
+        .into_iter()
+        .map(Pixel::from)
+        .collect();
+    assert_eq!(pixels, expected);
+}
+
+#[test]
+fn test_invalid() {
+    let bmp = build_bmp(&info_header(2, 2, 24, 0, 0), &[], &[0; 16]);
+    assert!(decode(&bmp).is_ok());
+    assert!(matches!(decode(&bmp[1..]), Err(BmpError::InvalidMagic)));
+    assert!(matches!(decode(&bmp[..60]), Err(BmpError::UnexpectedEnd)));
+    assert!(matches!(decode(&bmp[..30]), Err(BmpError::UnexpectedEnd)));
+    // A tiny file claiming a huge image fails before the pixels are allocated
+    let bmp = build_bmp(&info_header(20000, 20000, 24, 0, 0), &[], &[0; 16]);
+    assert!(matches!(decode(&bmp), Err(BmpError::UnexpectedEnd)));
+
+    let bmp = build_bmp(&info_header(0, 2, 24, 0, 0), &[], &[]);
+    assert!(matches!(decode(&bmp), Err(BmpError::InvalidDimensions(_))));
+    let bmp = build_bmp(&info_header(-2, 2, 24, 0, 0), &[], &[0; 16]);
+    assert!(matches!(decode(&bmp), Err(BmpError::InvalidHeader)));
+    let bmp = build_bmp(&info_header(1, 1, 24, 4, 0), &[], &[0; 4]);
+    assert!(matches!(
+        decode(&bmp),
+        Err(BmpError::UnsupportedFormat {
+            bits_per_pixel: 24,
+            compression: 4
+        })
+    ));
+}
@@ -0,0 +1,36 @@
+use qoi::{
+    decode::decode_allocated,
+    encode::{encode_allocated, encode_allocated_with, EncodeOptions},
+    ColorChannel, ColorSpace, Header, Pixel,
+};
+
+#[test]
+fn test_canonical_transparent() {
+    let header = Header::new(64, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let pixels: Vec<Pixel> = (0..64u8)
+        .map(|i| match i % 8 {
+            0 => Pixel::from([255, 0, 0, 255]),
+            _ => Pixel::from([i.wrapping_mul(37), i, 255 - i, 0]),
+        })
+        .collect();
+    let options = EncodeOptions {
+        canonical_transparent: true,
+    };
+    let plain = encode_allocated(&header, &pixels).unwrap();
+    let canonical = encode_allocated_with(&header, &pixels, options).unwrap();
+    assert!(canonical.len() < plain.len());
+
+    let (_, decoded) = decode_allocated(&canonical).unwrap();
+    for (px, dec) in pixels.iter().zip(&decoded) {
+        match px.a {
+            0 => assert_eq!(*dec, Pixel::zero()),
+            _ => assert_eq!(dec, px),
+        }
+    }
+    // Without transparent pixels, the output doesn't change
+    let opaque: Vec<Pixel> = pixels.iter().map(|&px| Pixel { a: 255, ..px }).collect();
+    assert_eq!(
+        encode_allocated(&header, &opaque).unwrap(),
+        encode_allocated_with(&header, &opaque, options).unwrap()
+    );
+}
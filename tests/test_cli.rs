@@ -0,0 +1,345 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+fn qoi(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_qoi"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn tmp_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+#[test]
+fn test_cli_info() {
+    let res = qoi(&["info", "./imgs/testcard.qoi"]);
+    assert!(res.status.success());
+    let stdout = String::from_utf8(res.stdout).unwrap();
+    assert!(stdout.contains("256x256"));
+    assert!(stdout.contains("RGBA"));
+}
+
+#[test]
+fn test_cli_validate() {
+    assert_eq!(qoi(&["validate", "./imgs/dice.qoi"]).status.code(), Some(0));
+
+    let truncated = tmp_path("truncated.qoi");
+    fs::write(&truncated, &fs::read("./imgs/dice.qoi").unwrap()[..1000]).unwrap();
+    assert_eq!(
+        qoi(&["validate", truncated.to_str().unwrap()])
+            .status
+            .code(),
+        Some(1)
+    );
+
+    assert_eq!(
+        qoi(&["validate", "./imgs/missing.qoi"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(qoi(&["validate"]).status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_leading_run() {
+    // A 2x2 image of {0,0,0,255} as encoded by the reference, which starts with a run
+    let encoded = tmp_path("leading_run.qoi");
+    let decoded = tmp_path("leading_run.bin");
+    let mut bytes = qoi::MAGIC.to_vec();
+    bytes.extend_from_slice(&[0, 0, 0, 2, 0, 0, 0, 2, 4, 0, 0xc3]);
+    bytes.extend_from_slice(&qoi::STREAM_END);
+    fs::write(&encoded, bytes).unwrap();
+
+    assert_eq!(
+        qoi(&["validate", encoded.to_str().unwrap()]).status.code(),
+        Some(0)
+    );
+    let res = qoi(&[
+        "decode",
+        encoded.to_str().unwrap(),
+        decoded.to_str().unwrap(),
+    ]);
+    assert!(
+        res.status.success(),
+        "{}",
+        String::from_utf8_lossy(&res.stderr)
+    );
+    assert_eq!(fs::read(&decoded).unwrap(), [0, 0, 0, 255].repeat(4));
+}
+
+#[test]
+fn test_cli_raw_roundtrip() {
+    let encoded = tmp_path("testcard.qoi");
+    let decoded = tmp_path("testcard.bin");
+    let res = qoi(&[
+        "encode",
+        "./imgs/testcard.bin",
+        encoded.to_str().unwrap(),
+        "--width",
+        "256",
+        "--height",
+        "256",
+    ]);
+    assert!(
+        res.status.success(),
+        "{}",
+        String::from_utf8_lossy(&res.stderr)
+    );
+    assert_eq!(
+        fs::read(&encoded).unwrap(),
+        fs::read("./imgs/testcard.qoi").unwrap()
+    );
+
+    let res = qoi(&[
+        "decode",
+        encoded.to_str().unwrap(),
+        decoded.to_str().unwrap(),
+    ]);
+    assert!(
+        res.status.success(),
+        "{}",
+        String::from_utf8_lossy(&res.stderr)
+    );
+    assert_eq!(
+        fs::read(&decoded).unwrap(),
+        fs::read("./imgs/testcard.bin").unwrap()
+    );
+}
+
+#[test]
+fn test_cli_convert() {
+    let converted = tmp_path("dice.qoi");
+    let res = qoi(&["convert", "./imgs/dice.png", converted.to_str().unwrap()]);
+    assert!(res.status.success());
+    assert_eq!(
+        qoi::read(&converted).unwrap().1,
+        qoi::read("./imgs/dice.qoi").unwrap().1
+    );
+
+    let res = qoi(&["convert", "./imgs/dice.png", "dice.gif"]);
+    assert_eq!(res.status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_convert_pam() {
+    let pam = tmp_path("testcard_rgba.pam");
+    let converted = tmp_path("testcard_rgba.qoi");
+    assert!(
+        qoi(&["convert", "./imgs/testcard_rgba.qoi", pam.to_str().unwrap()])
+            .status
+            .success()
+    );
+    assert!(qoi(&[
+        "convert",
+        pam.to_str().unwrap(),
+        converted.to_str().unwrap()
+    ])
+    .status
+    .success());
+    assert_eq!(
+        fs::read(&converted).unwrap(),
+        fs::read("./imgs/testcard_rgba.qoi").unwrap()
+    );
+}
+
+#[test]
+fn test_cli_batch() {
+    let root = tmp_path("batch");
+    let _ = fs::remove_dir_all(&root);
+    let (input, output) = (root.join("in"), root.join("out"));
+    fs::create_dir_all(input.join("sub")).unwrap();
+    fs::copy("./imgs/dice.png", input.join("dice.png")).unwrap();
+    fs::copy("./imgs/testcard.png", input.join("sub/testcard.png")).unwrap();
+    fs::write(input.join("notes.txt"), "not an image").unwrap();
+
+    let batch = |input: &Path, output: &Path, to: &str| {
+        let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+        qoi(&["batch", input, output, "--to", to, "--threads", "2"])
+    };
+    let res = batch(&input, &output, "qoi");
+    assert!(res.status.success());
+    assert_eq!(
+        qoi::read(output.join("sub/testcard.qoi")).unwrap().1,
+        qoi::read("./imgs/testcard.qoi").unwrap().1
+    );
+    assert!(output.join("dice.qoi").exists());
+    assert!(!output.join("notes.qoi").exists());
+
+    // Up-to-date outputs are skipped
+    let res = batch(&input, &output, "qoi");
+    let stdout = String::from_utf8(res.stdout).unwrap();
+    let total = stdout
+        .lines()
+        .find(|line| line.starts_with("total"))
+        .unwrap();
+    let columns: Vec<&str> = total.split_whitespace().collect();
+    assert_eq!(columns[1..5], ["2", "0", "2", "0"]);
+
+    // And back to PNG
+    let back = root.join("back");
+    assert!(batch(&output, &back, "png").status.success());
+    assert!(back.join("sub/testcard.png").exists());
+
+    fs::write(input.join("broken.png"), "not a png").unwrap();
+    let res = batch(&input, &output, "qoi");
+    assert_eq!(res.status.code(), Some(1));
+    let stdout = String::from_utf8(res.stdout).unwrap();
+    assert!(stdout.contains("failed: "));
+    assert!(stdout.contains("broken.png"));
+}
+
+#[test]
+fn test_cli_diff() {
+    let res = qoi(&["diff", "./imgs/dice.png", "./imgs/dice.qoi"]);
+    assert_eq!(res.status.code(), Some(0));
+
+    let out = tmp_path("diff.png");
+    let res = qoi(&[
+        "diff",
+        "./imgs/testcard.qoi",
+        "./imgs/testcard_rgba.qoi",
+        "--out",
+        out.to_str().unwrap(),
+    ]);
+    assert_eq!(res.status.code(), Some(1));
+    let stdout = String::from_utf8(res.stdout).unwrap();
+    assert!(stdout.contains("first mismatch"));
+    let (header, _) = qoi::png::read(&out).unwrap();
+    assert_eq!((header.width, header.height), (256, 256));
+
+    let res = qoi(&["diff", "./imgs/dice.qoi", "./imgs/testcard.qoi"]);
+    assert_eq!(res.status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_stats() {
+    let res = qoi(&["stats", "./imgs/dice.qoi"]);
+    assert!(res.status.success());
+    let stdout = String::from_utf8(res.stdout).unwrap();
+    assert!(stdout.contains("QOI_OP_LUMA"));
+    assert!(stdout.contains("index hits"));
+    assert_eq!(qoi(&["stats", "./imgs/dice.png"]).status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_disasm() {
+    let res = qoi(&["disasm", "./imgs/dice.qoi"]);
+    assert!(res.status.success());
+    let stdout = String::from_utf8(res.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert!(lines.next().unwrap().contains("800x600"));
+    assert_eq!(
+        lines.next().unwrap().trim_end(),
+        "0000000e  00              QOI_OP_INDEX index 0                     (0, 0)"
+    );
+    assert!(stdout.trim_end().ends_with("end marker"));
+
+    let truncated = tmp_path("disasm_truncated.qoi");
+    fs::write(&truncated, &fs::read("./imgs/dice.qoi").unwrap()[..1000]).unwrap();
+    let res = qoi(&["disasm", truncated.to_str().unwrap()]);
+    assert_eq!(res.status.code(), Some(1));
+    assert!(String::from_utf8(res.stdout)
+        .unwrap()
+        .contains("error: Missing Data"));
+}
+
+#[test]
+fn test_cli_asm() {
+    let input = tmp_path("asm.txt");
+    let output = tmp_path("asm.qoi");
+    fs::write(&input, "HEADER 2 2 3 0\nRGB 0 128 255\nRUN 3\n").unwrap();
+    let res = qoi(&["asm", input.to_str().unwrap(), output.to_str().unwrap()]);
+    assert!(res.status.success());
+    let (header, pixels) = qoi::read(&output).unwrap();
+    assert_eq!((header.width, header.height), (2, 2));
+    assert_eq!(pixels, vec![qoi::Pixel::from([0, 128, 255]); 4]);
+
+    fs::write(&input, "HEADER 2 2 3 0\nRUN 0\n").unwrap();
+    let res = qoi(&["asm", input.to_str().unwrap(), output.to_str().unwrap()]);
+    assert_eq!(res.status.code(), Some(2));
+    assert!(String::from_utf8(res.stderr).unwrap().contains("Line 2"));
+}
+
+#[test]
+fn test_cli_crop() {
+    let output = tmp_path("cli_crop.qoi");
+    let out = output.to_str().unwrap();
+    let args = [
+        "crop",
+        "./imgs/dice.qoi",
+        out,
+        "--x",
+        "10",
+        "--y",
+        "20",
+        "--width",
+        "30",
+        "--height",
+        "40",
+    ];
+    assert!(qoi(&args).status.success());
+    let (header, _) = qoi::read(&output).unwrap();
+    assert_eq!((header.width, header.height), (30, 40));
+
+    let args = [
+        "crop",
+        "./imgs/dice.qoi",
+        out,
+        "--x",
+        "790",
+        "--y",
+        "0",
+        "--width",
+        "30",
+        "--height",
+        "40",
+    ];
+    assert_eq!(qoi(&args).status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_resize() {
+    let output = tmp_path("cli_resize.png");
+    let out = output.to_str().unwrap();
+    let res = qoi(&[
+        "resize",
+        "./imgs/dice.qoi",
+        out,
+        "--width",
+        "200",
+        "--filter",
+        "box",
+    ]);
+    assert!(res.status.success());
+    let (header, _) = qoi::png::read(&output).unwrap();
+    assert_eq!((header.width, header.height), (200, 150));
+
+    assert_eq!(
+        qoi(&["resize", "./imgs/dice.qoi", out]).status.code(),
+        Some(2)
+    );
+    let res = qoi(&[
+        "resize",
+        "./imgs/dice.qoi",
+        out,
+        "--width",
+        "9",
+        "--filter",
+        "cubic",
+    ]);
+    assert_eq!(res.status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_convert_colorspace() {
+    let output = tmp_path("cli_linear.qoi");
+    let out = output.to_str().unwrap();
+    let res = qoi(&["convert", "./imgs/dice.qoi", out, "--colorspace", "linear"]);
+    assert!(res.status.success());
+    let (header, _) = qoi::read(&output).unwrap();
+    assert_eq!(header.colorspace, qoi::ColorSpace::LINEAR);
+}
@@ -0,0 +1,91 @@
+use qoi::{color::*, image::Image, ColorChannel, ColorSpace, Header, Pixel};
+
+#[test]
+fn test_transfer_functions() {
+    assert_eq!(srgb_to_linear(0), 0);
+    assert_eq!(srgb_to_linear(128), 55);
+    assert_eq!(srgb_to_linear(255), 255);
+    assert_eq!(linear_to_srgb(128), 188);
+    assert_eq!(linear_to_srgb(1), 13);
+    for v in 0..=255u8 {
+        // sRGB has more precision in dark tones, so this direction is almost lossless
+        assert!(srgb_to_linear(linear_to_srgb(v)).abs_diff(v) <= 1);
+        let f = v as f32 / 255.0;
+        assert!((linear_to_srgb_f32(srgb_to_linear_f32(f)) - f).abs() < 1e-5);
+        assert_eq!(srgb_to_linear_table()[v as usize], srgb_to_linear_f32(f));
+    }
+}
+
+#[test]
+fn test_convert_colorspace() {
+    let mut header = Header::new(2, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let mut pixels = vec![
+        Pixel::from([128, 255, 0, 128]),
+        Pixel::from([188, 10, 64, 7]),
+    ];
+    convert_colorspace(&mut header, &mut pixels, ColorSpace::LINEAR).unwrap();
+    assert_eq!(header.colorspace, ColorSpace::LINEAR);
+    assert_eq!(
+        pixels,
+        [Pixel::from([55, 255, 0, 128]), Pixel::from([128, 1, 13, 7])]
+    );
+
+    // Already linear
+    let before = pixels.clone();
+    convert_colorspace(&mut header, &mut pixels, ColorSpace::LINEAR).unwrap();
+    assert_eq!(pixels, before);
+
+    convert_colorspace(&mut header, &mut pixels, ColorSpace::SRGB).unwrap();
+    assert_eq!(header.colorspace, ColorSpace::SRGB);
+    assert_eq!(pixels[0], Pixel::from([128, 255, 0, 128]));
+
+    assert!(matches!(
+        convert_colorspace(&mut header, &mut pixels[..1], ColorSpace::LINEAR),
+        Err(ColorError::MissingPixels { .. })
+    ));
+}
+
+#[test]
+fn test_image_convert_colorspace() {
+    let mut img = Image::open("./imgs/dice.qoi").unwrap();
+    let original = img.clone();
+    img.convert_colorspace(ColorSpace::LINEAR);
+    assert_eq!(img.colorspace(), ColorSpace::LINEAR);
+    let alpha = |img: &Image| img.pixels().iter().map(|px| px.a).collect::<Vec<_>>();
+    assert_eq!(alpha(&img), alpha(&original));
+    assert_ne!(img.pixels(), original.pixels());
+}
+
+#[test]
+fn test_premultiply() {
+    let mut pixels = vec![
+        Pixel::from([255, 128, 1, 128]),
+        Pixel::from([200, 100, 50, 255]),
+        Pixel::from([9, 8, 7, 0]),
+    ];
+    premultiply(&mut pixels);
+    assert_eq!(
+        pixels,
+        [
+            Pixel::from([128, 64, 1, 128]),
+            Pixel::from([200, 100, 50, 255]),
+            Pixel::from([0, 0, 0, 0])
+        ]
+    );
+    // Exact rounding of c * a / 255 for all values
+    for c in 0..=255u8 {
+        for a in 0..=255u8 {
+            let mut px = [Pixel::from([c, c, c, a])];
+            premultiply(&mut px);
+            let expected = (c as f32 * a as f32 / 255.0).round() as u8;
+            assert_eq!(px[0].r, expected, "{} {}", c, a);
+            unpremultiply(&mut px);
+            if a == 255 {
+                assert_eq!(px[0].r, c);
+            }
+        }
+    }
+    unpremultiply(&mut pixels);
+    assert_eq!(pixels[0], Pixel::from([255, 128, 2, 128]));
+    assert_eq!(pixels[2], Pixel::zero());
+}
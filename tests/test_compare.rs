@@ -0,0 +1,63 @@
+use qoi::{compare::*, ColorChannel, ColorSpace, Header, Pixel};
+
+#[test]
+fn test_identical() {
+    let (header, pixels) = qoi::read("./imgs/dice.qoi").unwrap();
+    let res = compare((&header, &pixels), (&header, &pixels)).unwrap();
+    assert!(res.is_identical());
+    assert_eq!(res.first_mismatch, None);
+    assert_eq!(res.max_error, [0; 4]);
+    assert_eq!(res.psnr, f64::INFINITY);
+}
+
+#[test]
+fn test_mismatches() {
+    let header = Header::new(4, 2, ColorChannel::RGB, ColorSpace::SRGB);
+    let a = vec![Pixel::from([100, 100, 100]); 8];
+    let mut b = a.clone();
+    b[6] = Pixel::from([110, 100, 97]);
+    b[7] = Pixel::from([100, 90, 100]);
+
+    let res = compare((&header, &a), (&header, &b)).unwrap();
+    assert_eq!(res.mismatches, 2);
+    assert_eq!(res.first_mismatch, Some((2, 1)));
+    assert_eq!(res.max_error, [10, 10, 3, 0]);
+    // MSE = (100 + 9 + 100) / 24
+    let mse: f64 = 209.0 / 24.0;
+    assert!((res.psnr - 10.0 * (255.0 * 255.0 / mse).log10()).abs() < 1e-9);
+
+    // Alpha counts once one of the images has an alpha channel
+    let rgba = Header::new(4, 2, ColorChannel::RGBA, ColorSpace::SRGB);
+    b[7].a = 0;
+    let res = compare((&header, &a), (&rgba, &b)).unwrap();
+    assert_eq!(res.max_error, [10, 10, 3, 255]);
+    let mse: f64 = (209.0 + 255.0 * 255.0) / 32.0;
+    assert!((res.psnr - 10.0 * (255.0 * 255.0 / mse).log10()).abs() < 1e-9);
+
+    let (diff_header, diff) = diff_image((&header, &a), (&header, &b)).unwrap();
+    assert_eq!((diff_header.width, diff_header.height), (4, 2));
+    assert_eq!(diff[6], Pixel::from([133, 0, 0]));
+    assert_eq!(diff[7], Pixel::from([255, 0, 0]));
+    assert_eq!(diff[0], Pixel::from([185, 185, 185]));
+}
+
+#[test]
+fn test_invalid() {
+    let a = Header::new(4, 2, ColorChannel::RGB, ColorSpace::SRGB);
+    let b = Header::new(2, 4, ColorChannel::RGB, ColorSpace::SRGB);
+    let pixels = vec![Pixel::def(); 8];
+    assert!(matches!(
+        compare((&a, &pixels), (&b, &pixels)),
+        Err(CompareError::DimensionMismatch {
+            first: (4, 2),
+            second: (2, 4)
+        })
+    ));
+    assert!(matches!(
+        compare((&a, &pixels), (&a, &pixels[1..])),
+        Err(CompareError::MissingPixels {
+            expected_size: 8,
+            received_size: 7
+        })
+    ));
+}
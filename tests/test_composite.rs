@@ -0,0 +1,103 @@
+use qoi::{
+    composite::Operator,
+    image::{Image, ImageView},
+    ColorChannel, ColorSpace, Header, Pixel,
+};
+
+const LINEAR: ColorSpace = ColorSpace::LINEAR;
+
+#[test]
+fn test_porter_duff() {
+    let red = Pixel::from([255, 0, 0, 255]);
+    let half_blue = Pixel::from([0, 0, 255, 128]);
+    let clear = Pixel::from([10, 20, 30, 0]);
+
+    assert_eq!(red.composite(half_blue, Operator::Over, LINEAR), red);
+    assert_eq!(
+        half_blue.composite(red, Operator::Over, LINEAR),
+        Pixel::from([127, 0, 128, 255])
+    );
+    assert_eq!(clear.composite(red, Operator::Over, LINEAR), red);
+    assert_eq!(red.composite(clear, Operator::Over, LINEAR), red);
+
+    assert_eq!(
+        red.composite(half_blue, Operator::In, LINEAR),
+        Pixel::from([255, 0, 0, 128])
+    );
+    assert_eq!(
+        red.composite(half_blue, Operator::Out, LINEAR),
+        Pixel::from([255, 0, 0, 127])
+    );
+    assert_eq!(
+        half_blue.composite(red, Operator::Atop, LINEAR),
+        Pixel::from([127, 0, 128, 255])
+    );
+    assert_eq!(
+        red.composite(half_blue, Operator::Atop, LINEAR),
+        Pixel::from([255, 0, 0, 128])
+    );
+    assert_eq!(red.composite(red, Operator::Xor, LINEAR), Pixel::zero());
+    assert_eq!(clear.composite(red, Operator::In, LINEAR), Pixel::zero());
+}
+
+#[test]
+fn test_blend_modes() {
+    let gray = Pixel::from([128, 128, 128, 255]);
+    let color = Pixel::from([255, 51, 204, 255]);
+    let white = Pixel::from([255, 255, 255, 255]);
+    let black = Pixel::from([0, 0, 0, 255]);
+
+    assert_eq!(white.composite(color, Operator::Multiply, LINEAR), color);
+    assert_eq!(black.composite(color, Operator::Screen, LINEAR), color);
+    assert_eq!(
+        gray.composite(color, Operator::Multiply, LINEAR),
+        Pixel::from([128, 26, 102, 255])
+    );
+    assert_eq!(
+        gray.composite(color, Operator::Screen, LINEAR),
+        Pixel::from([255, 153, 230, 255])
+    );
+    // Overlaying 50% gray keeps the destination
+    assert_eq!(
+        gray.composite(color, Operator::Overlay, LINEAR),
+        Pixel::from([255, 51, 204, 255])
+    );
+    // Transparent sources leave the destination untouched
+    let clear = Pixel::from([0, 0, 0, 0]);
+    for op in [Operator::Multiply, Operator::Screen, Operator::Overlay] {
+        assert_eq!(clear.composite(color, op, LINEAR), color);
+    }
+}
+
+#[test]
+fn test_linear_light() {
+    // Half transparent white over black reflects half the light, which is 188 in sRGB
+    let white = Pixel::from([255, 255, 255, 128]);
+    let black = Pixel::from([0, 0, 0, 255]);
+    let res = white.composite(black, Operator::Over, ColorSpace::SRGB);
+    assert_eq!(res, Pixel::from([188, 188, 188, 255]));
+    let res = white.composite(black, Operator::Over, LINEAR);
+    assert_eq!(res, Pixel::from([128, 128, 128, 255]));
+}
+
+#[test]
+fn test_composite_image() {
+    let header = Header::new(4, 3, ColorChannel::RGBA, LINEAR);
+    let bg = Pixel::from([0, 0, 255, 255]);
+    let mut image = Image::filled(header, bg).unwrap();
+    let mark = [Pixel::from([255, 0, 0, 255]); 4];
+    let view = ImageView::new(&mark, 2, 2).unwrap();
+
+    // Clipped at the right & bottom edge
+    image.composite(&view, 3, 2, Operator::Over);
+    image.composite(&view, 4, 0, Operator::Over);
+    for (x, y, &px) in image.enumerate_pixels() {
+        match (x, y) {
+            (3, 2) => assert_eq!(px, mark[0]),
+            _ => assert_eq!(px, bg),
+        }
+    }
+
+    let mut view_mut = image.view_mut();
+    assert!(view_mut.composite(&view, Operator::Over, LINEAR).is_err());
+}
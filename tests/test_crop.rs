@@ -0,0 +1,76 @@
+use qoi::{crop::*, image::Image};
+use std::{fs, io::Read};
+
+// Hands out at most 3 bytes per read, so chunks are split across reads
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(self.0.len()).min(3);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_crop() {
+    let buffer = fs::read("./imgs/testcard_rgba.qoi").unwrap();
+    let img = Image::decode(&buffer).unwrap();
+    for (x, y, width, height) in [
+        (0, 0, 256, 256),
+        (10, 20, 100, 50),
+        (255, 255, 1, 1),
+        (0, 100, 256, 1),
+    ] {
+        let expected = img
+            .view()
+            .sub_view(x, y, width, height)
+            .unwrap()
+            .encode(img.channels(), img.colorspace())
+            .unwrap();
+        assert_eq!(crop(&buffer[..], x, y, width, height).unwrap(), expected);
+        assert_eq!(
+            crop(Trickle(&buffer), x, y, width, height).unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_crop_file() {
+    let output = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("crop.qoi");
+    let size = crop_file("./imgs/dice.qoi", &output, 300, 200, 100, 80).unwrap();
+    assert_eq!(fs::metadata(&output).unwrap().len() as usize, size);
+    let cropped = Image::open(&output).unwrap();
+    let img = Image::open("./imgs/dice.qoi").unwrap();
+    assert_eq!((cropped.width(), cropped.height()), (100, 80));
+    assert_eq!(cropped.get_pixel(0, 0), img.get_pixel(300, 200));
+    assert_eq!(cropped.get_pixel(99, 79), img.get_pixel(399, 279));
+}
+
+#[test]
+fn test_crop_errors() {
+    let buffer = fs::read("./imgs/testcard.qoi").unwrap();
+    for (x, y, width, height) in [
+        (0, 0, 0, 10),
+        (200, 0, 57, 1),
+        (0, 256, 1, 1),
+        (u32::MAX, 0, 2, 2),
+    ] {
+        assert!(matches!(
+            crop(&buffer[..], x, y, width, height),
+            Err(CropError::InvalidRegion { .. })
+        ));
+    }
+    assert!(matches!(
+        crop(&buffer[..10], 0, 0, 1, 1),
+        Err(CropError::Decode(qoi::decode::DecodeError::MissingHeader))
+    ));
+    // Only the input up to the region's last row is needed
+    assert!(crop(&buffer[..buffer.len() / 2], 0, 0, 256, 10).is_ok());
+    assert!(matches!(
+        crop(&buffer[..buffer.len() / 2], 0, 200, 256, 10),
+        Err(CropError::Decode(_))
+    ));
+}
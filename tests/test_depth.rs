@@ -0,0 +1,95 @@
+use qoi::{decode::decode_allocated, depth::*, ColorChannel, ColorSpace, Header, Pixel};
+
+const DITHERS: [Dither; 4] = [
+    Dither::Round,
+    Dither::Truncate,
+    Dither::Ordered,
+    Dither::FloydSteinberg,
+];
+
+#[test]
+fn test_depth_exact() {
+    // Samples expanded from 8 bits are kept as they are
+    let header = Header::new(16, 16, ColorChannel::RGBA, ColorSpace::SRGB);
+    let bytes: Vec<u8> = (0..1024).map(|i| (i * 7 % 256) as u8).collect();
+    let samples: Vec<u16> = bytes.iter().map(|&v| v as u16 * 257).collect();
+    for dither in DITHERS {
+        let pixels = downconvert(&header, &samples, dither).unwrap();
+        let res: Vec<u8> = pixels
+            .iter()
+            .flat_map(|px| [px.r, px.g, px.b, px.a])
+            .collect();
+        assert_eq!(res, bytes, "{:?}", dither);
+    }
+}
+
+#[test]
+fn test_depth_rounding() {
+    let header = Header::new(3, 1, ColorChannel::RGB, ColorSpace::SRGB);
+    let samples = [0, 128, 129, 385, 386, 65535, 65403, 65408, 1000];
+    let round = downconvert(&header, &samples, Dither::Round).unwrap();
+    assert_eq!(
+        round,
+        [
+            Pixel::from([0, 0, 1]),
+            Pixel::from([1, 2, 255]),
+            Pixel::from([254, 255, 4])
+        ]
+    );
+    let truncate = downconvert(&header, &samples, Dither::Truncate).unwrap();
+    assert_eq!(
+        truncate,
+        [
+            Pixel::from([0, 0, 0]),
+            Pixel::from([1, 1, 255]),
+            Pixel::from([255, 255, 3])
+        ]
+    );
+}
+
+#[test]
+fn test_depth_dither() {
+    // A value between two 8-bit levels is dithered, so that the average matches it
+    let header = Header::new(64, 64, ColorChannel::RGB, ColorSpace::LINEAR);
+    let value = 100 * 257 + 64;
+    let samples = vec![value; header.pixel_amount() * 3];
+    for dither in [Dither::Ordered, Dither::FloydSteinberg] {
+        let pixels = downconvert(&header, &samples, dither).unwrap();
+        assert!(pixels.iter().all(|px| px.r == 100 || px.r == 101));
+        let mean = pixels.iter().map(|px| px.r as f64).sum::<f64>() / pixels.len() as f64;
+        assert!((mean - value as f64 / 257.0).abs() < 0.01, "{:?}", dither);
+    }
+    let pixels = downconvert(&header, &samples, Dither::Round).unwrap();
+    assert!(pixels.iter().all(|px| px.r == 100));
+
+    let encoded = encode(&header, &samples, Dither::Ordered).unwrap();
+    let (_, decoded) = decode_allocated(&encoded).unwrap();
+    assert_eq!(
+        decoded,
+        downconvert(&header, &samples, Dither::Ordered).unwrap()
+    );
+}
+
+#[test]
+fn test_depth_errors() {
+    let header = Header::new(2, 2, ColorChannel::RGBA, ColorSpace::SRGB);
+    assert!(matches!(
+        downconvert(&header, &[0; 12], Dither::Round),
+        Err(DepthError::SizeMismatch {
+            expected_size: 16,
+            received_size: 12
+        })
+    ));
+}
+
+#[test]
+fn test_round_matches_u16_to_u8() {
+    let samples: Vec<u16> = (0..=u16::MAX).collect();
+    let header = Header::new(65536 / 4, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let pixels = downconvert(&header, &samples, Dither::Round).unwrap();
+    let res = pixels.iter().flat_map(|px| [px.r, px.g, px.b, px.a]);
+    for (v, byte) in samples.into_iter().zip(res) {
+        assert_eq!(byte, qoi::u16_to_u8(v));
+        assert_eq!(byte, (v as f64 / 257.0).round() as u8);
+    }
+}
@@ -0,0 +1,75 @@
+use qoi::{decode::DecodeError, disasm::*, Opcode, HEADER_SIZE, STREAM_END_SIZE};
+use std::fs;
+
+#[test]
+fn test_chunk_from_bytes() {
+    let cases = [
+        (vec![0xfe, 1, 2, 3], Chunk::RGB([1, 2, 3])),
+        (vec![0xff, 1, 2, 3, 4], Chunk::RGBA([1, 2, 3, 4])),
+        (vec![0x05], Chunk::INDEX(5)),
+        (
+            vec![0x4e],
+            Chunk::DIFF {
+                dr: -2,
+                dg: 1,
+                db: 0,
+            },
+        ),
+        (
+            vec![0x9d, 0x9a],
+            Chunk::LUMA {
+                dg: -3,
+                dr_dg: 1,
+                db_dg: 2,
+            },
+        ),
+        (vec![0xcb], Chunk::RUN(12)),
+    ];
+    for (bytes, chunk) in cases {
+        assert_eq!(Chunk::from_bytes(&bytes), Some(chunk));
+        assert_eq!(Opcode::from_byte(bytes[0]), chunk.opcode());
+    }
+    assert_eq!(Chunk::from_bytes(&[0xfe, 1, 2]), None);
+    assert_eq!(
+        Chunk::LUMA {
+            dg: -3,
+            dr_dg: 1,
+            db_dg: 2
+        }
+        .to_string(),
+        "QOI_OP_LUMA  dg -3 dr-dg 1 db-dg 2"
+    );
+}
+
+#[test]
+fn test_disassemble() {
+    let buffer = fs::read("./imgs/testcard.qoi").unwrap();
+    let res = disassemble(&buffer).unwrap();
+    assert!(res.error.is_none());
+    assert_eq!(res.lines[0].offset, HEADER_SIZE);
+    assert_eq!(res.lines[0].first, (0, 0));
+    assert_eq!(res.end, buffer.len() - STREAM_END_SIZE);
+    assert_eq!(res.lines.last().unwrap().last, (255, 255));
+
+    // The lines are contiguous in the buffer & the image
+    for pair in res.lines.windows(2) {
+        assert_eq!(pair[0].offset + pair[0].bytes.len(), pair[1].offset);
+        let (x, y) = pair[0].last;
+        let next = if x == 255 { (0, y + 1) } else { (x + 1, y) };
+        assert_eq!(next, pair[1].first);
+    }
+}
+
+#[test]
+fn test_disassemble_truncated() {
+    let buffer = fs::read("./imgs/testcard.qoi").unwrap();
+    let res = disassemble(&buffer[..200]).unwrap();
+    assert!(matches!(res.error, Some(DecodeError::MissingPixels { .. })));
+    let last = res.lines.last().unwrap();
+    assert_eq!(last.offset + last.bytes.len(), res.end);
+    assert!(res.end <= 200);
+
+    let res = disassemble(&buffer[..buffer.len() - 1]).unwrap();
+    assert!(matches!(res.error, Some(DecodeError::MissingEndMarker)));
+    assert!(disassemble(&buffer[..10]).is_err());
+}
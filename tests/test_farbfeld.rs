@@ -0,0 +1,72 @@
+use qoi::{farbfeld::*, ColorChannel, Pixel};
+use std::{fs, path::Path};
+
+#[test]
+fn test_roundtrip() {
+    for path in fs::read_dir(Path::new("./imgs")).unwrap() {
+        let path = path.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "qoi") {
+            continue;
+        }
+        let (header, pixels) = qoi::read(&path).unwrap();
+        let encoded = encode(&header, &pixels).unwrap();
+        assert_eq!(encoded.len(), FARBFELD_HEADER_SIZE + pixels.len() * 8);
+        let (res_header, res_pixels) = decode(&encoded).unwrap();
+        assert_eq!(
+            (res_header.width, res_header.height),
+            (header.width, header.height)
+        );
+        assert_eq!(res_header.channels, ColorChannel::RGBA);
+        assert_eq!(res_pixels, pixels);
+    }
+}
+
+#[test]
+fn test_rounding() {
+    let mut data = b"farbfeld\0\0\0\x02\0\0\0\x01".to_vec();
+    // 0x8100 is just below 128.5 * 257, 0x8101 just above
+    data.extend_from_slice(&[0x81, 0x00, 0x81, 0x01, 0x00, 0x80, 0xff, 0xff]);
+    data.extend_from_slice(&[0x00, 0x00, 0x01, 0x01, 0xfe, 0xfe, 0x00, 0x81]);
+    let (_, pixels) = decode(&data).unwrap();
+    assert_eq!(
+        pixels,
+        vec![Pixel::from([128, 129, 0, 255]), Pixel::from([0, 1, 254, 1])]
+    );
+}
+
+#[test]
+fn test_invalid() {
+    assert!(matches!(
+        decode(b"farbfeld"),
+        Err(FarbfeldError::MissingHeader)
+    ));
+    assert!(matches!(
+        decode(b"farbfelt\0\0\0\x01\0\0\0\x01"),
+        Err(FarbfeldError::InvalidMagic)
+    ));
+    assert!(matches!(
+        decode(b"farbfeld\0\0\0\0\0\0\0\x01"),
+        Err(FarbfeldError::InvalidDimensions(_))
+    ));
+    assert!(matches!(
+        decode(b"farbfeld\0\0\0\x01\0\0\0\x01\0\0"),
+        Err(FarbfeldError::MissingPixels {
+            expected_size: 8,
+            received_size: 2
+        })
+    ));
+}
+
+#[test]
+fn test_rounding_matches_u16_to_u8() {
+    let samples: Vec<u16> = (0..=u16::MAX).collect();
+    let mut data = b"farbfeld".to_vec();
+    data.extend_from_slice(&(samples.len() as u32 / 4).to_be_bytes());
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend(samples.iter().flat_map(|v| v.to_be_bytes()));
+    let (_, pixels) = decode(&data).unwrap();
+    let res = pixels.iter().flat_map(|px| [px.r, px.g, px.b, px.a]);
+    for (v, byte) in samples.iter().zip(res) {
+        assert_eq!(byte, qoi::u16_to_u8(*v));
+    }
+}
@@ -0,0 +1,69 @@
+use qoi::{
+    decode::decode_allocated, encode::encode_allocated, gray::*, ColorChannel, ColorSpace, Header,
+    Pixel,
+};
+
+#[test]
+fn test_gray_roundtrip() {
+    let l: Vec<u8> = (0..=255).collect();
+    let encoded = encode(&l, 16, 16, GrayLayout::L, ColorSpace::LINEAR).unwrap();
+    let (header, pixels) = decode_allocated(&encoded).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    assert_eq!(pixels[7], Pixel::from([7, 7, 7, 255]));
+    let (header, layout, out) = decode(&encoded).unwrap();
+    assert_eq!((header.width, header.height), (16, 16));
+    assert_eq!(layout, GrayLayout::L);
+    assert_eq!(out, l);
+
+    let la: Vec<u8> = (0..=255).flat_map(|v| [v, 255 - v]).collect();
+    let encoded = encode(&la, 32, 8, GrayLayout::LA, ColorSpace::SRGB).unwrap();
+    let (header, layout, out) = decode(&encoded).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGBA);
+    assert_eq!(layout, GrayLayout::LA);
+    assert_eq!(out, la);
+}
+
+#[test]
+fn test_gray_collapse() {
+    let header = Header::new(2, 2, ColorChannel::RGBA, ColorSpace::SRGB);
+    let mut pixels = vec![Pixel::from([9, 9, 9, 255]); 4];
+    assert_eq!(gray_layout(&pixels), Some(GrayLayout::L));
+    pixels[1].a = 0;
+    assert_eq!(gray_layout(&pixels), Some(GrayLayout::LA));
+    assert_eq!(
+        collapse(&header, &pixels, GrayLayout::LA).unwrap(),
+        [9, 255, 9, 0, 9, 255, 9, 255]
+    );
+    assert_eq!(collapse(&header, &pixels, GrayLayout::L).unwrap(), [9; 4]);
+    assert!(matches!(
+        collapse(&header, &pixels[..3], GrayLayout::L),
+        Err(GrayError::MissingPixels {
+            expected_size: 4,
+            received_size: 3
+        })
+    ));
+
+    pixels[3].b = 10;
+    assert_eq!(gray_layout(&pixels), None);
+    assert!(matches!(
+        collapse(&header, &pixels, GrayLayout::L),
+        Err(GrayError::NotGray { x: 1, y: 1 })
+    ));
+    let encoded = encode_allocated(&header, &pixels).unwrap();
+    assert!(matches!(decode(&encoded), Err(GrayError::NotGray { .. })));
+}
+
+#[test]
+fn test_gray_errors() {
+    assert!(matches!(
+        encode(&[0; 5], 2, 2, GrayLayout::L, ColorSpace::SRGB),
+        Err(GrayError::SizeMismatch {
+            expected_size: 4,
+            received_size: 5
+        })
+    ));
+    assert!(matches!(
+        encode(&[], 0, 2, GrayLayout::LA, ColorSpace::SRGB),
+        Err(GrayError::InvalidDimensions(_))
+    ));
+}
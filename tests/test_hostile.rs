@@ -0,0 +1,99 @@
+// The decoders must never panic, no matter which bytes they receive.
+// `fuzz/` contains cargo-fuzz targets for a more thorough search.
+
+use qoi::{decode, scan, stripe, Pixel};
+use std::fs;
+
+fn decode_all(buf: &[u8]) {
+    let mut pixels = vec![Pixel::def(); 1 << 12];
+    let _ = decode::decode(buf, &mut pixels);
+    let _ = decode::decode(buf, &mut []);
+    if decode::decode_header(buf).is_ok_and(|header| header.pixel_amount() <= 1 << 22) {
+        let _ = decode::decode_allocated(buf);
+        let _ = stripe::decode_striped(buf);
+        let _ = scan::decode_parallel(buf);
+        let _ = scan::ScanIndex::scan(buf, 100);
+    }
+    let _ = scan::ScanIndex::from_bytes(buf);
+}
+
+fn header(width: u32, height: u32, channels: u8, colorspace: u8) -> Vec<u8> {
+    let mut buf = qoi::MAGIC.to_vec();
+    buf.extend_from_slice(&width.to_be_bytes());
+    buf.extend_from_slice(&height.to_be_bytes());
+    buf.extend_from_slice(&[channels, colorspace]);
+    buf
+}
+
+#[test]
+fn test_hostile_headers() {
+    for (width, height) in [
+        (0, 0),
+        (0, 5),
+        (u32::MAX, u32::MAX),
+        (u32::MAX, 1),
+        (20000, 20001),
+    ] {
+        let mut buf = header(width, height, 4, 0);
+        buf.extend_from_slice(&qoi::STREAM_END);
+        assert!(decode::decode_header(&buf).is_err());
+        decode_all(&buf);
+    }
+    for len in 0..qoi::HEADER_SIZE {
+        decode_all(&header(1, 1, 4, 0)[..len]);
+    }
+    decode_all(&header(1, 1, 5, 0));
+    decode_all(&header(1, 1, 4, 2));
+}
+
+#[test]
+fn test_hostile_scan() {
+    // The largest valid header with only a handful of chunks
+    let mut buf = header(20000, 20000, 4, 0);
+    buf.extend_from_slice(&[0xfe, 1, 2, 3, 0x55]);
+    buf.extend_from_slice(&qoi::STREAM_END);
+    let index = scan::ScanIndex::scan(&buf, 1);
+    assert_eq!(index.unwrap().checkpoints.len(), 3);
+}
+
+#[test]
+fn test_hostile_chunks() {
+    // Every possible first byte, including runs at the start & truncated multi-byte chunks
+    for byte in 0..=255u8 {
+        let mut buf = header(3, 2, 4, 0);
+        buf.push(byte);
+        decode_all(&buf);
+        buf.extend_from_slice(&[byte; 40]);
+        decode_all(&buf);
+    }
+}
+
+#[test]
+fn test_mutated_files() {
+    let plain = fs::read("./imgs/testcard_rgba.qoi").unwrap();
+    let (header, pixels) = decode::decode_allocated(&plain).unwrap();
+    let striped = stripe::encode_striped(&header, &pixels, 5).unwrap();
+    let index = scan::ScanIndex::scan(&plain, 1000).unwrap().to_bytes();
+    let mut seed = 0x2545F4914F6CDD1Du64;
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+
+    for original in [plain, striped, index] {
+        for len in (0..original.len()).step_by(997) {
+            decode_all(&original[..len]);
+        }
+        for _ in 0..100 {
+            let mut buf = original.clone();
+            for _ in 0..1 + next() % 8 {
+                // Favor the end of the buffer, where stripe tables are stored
+                let idx = buf.len() - 1 - (next() % buf.len()) / (1 + next() % 64);
+                buf[idx] = next() as u8;
+            }
+            decode_all(&buf);
+        }
+    }
+}
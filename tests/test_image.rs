@@ -0,0 +1,151 @@
+use qoi::{image::*, ColorChannel, ColorSpace, Header, Pixel};
+
+#[test]
+fn test_image_new() {
+    let header = Header::new(3, 2, ColorChannel::RGBA, ColorSpace::SRGB);
+    assert!(Image::new(header, vec![Pixel::def(); 6]).is_ok());
+    assert!(matches!(
+        Image::new(header, vec![Pixel::def(); 5]),
+        Err(ImageError::SizeMismatch {
+            expected_size: 6,
+            received_size: 5
+        })
+    ));
+    let empty = Header::new(0, 2, ColorChannel::RGBA, ColorSpace::SRGB);
+    assert!(matches!(
+        Image::filled(empty, Pixel::def()),
+        Err(ImageError::InvalidDimensions(_))
+    ));
+}
+
+#[test]
+fn test_image_pixels() {
+    let header = Header::new(3, 2, ColorChannel::RGB, ColorSpace::SRGB);
+    let mut img = Image::filled(header, Pixel::from([1, 2, 3])).unwrap();
+    let red = Pixel::from([255, 0, 0]);
+    assert_eq!(img.set_pixel(2, 1, red), Some(Pixel::from([1, 2, 3])));
+    assert_eq!(img.set_pixel(3, 1, red), None);
+    assert_eq!(img.get_pixel(2, 1), Some(red));
+    assert_eq!(img.get_pixel(0, 2), None);
+    assert_eq!(img.pixels()[5], red);
+
+    assert_eq!(img.rows().count(), 2);
+    assert_eq!(img.rows().nth(1).unwrap()[2], red);
+    let (x, y, _) = img
+        .enumerate_pixels()
+        .find(|(_, _, &px)| px == red)
+        .unwrap();
+    assert_eq!((x, y), (2, 1));
+}
+
+#[test]
+fn test_image_roundtrip() {
+    let img = Image::open("./imgs/dice.qoi").unwrap();
+    assert_eq!((img.width(), img.height()), (800, 600));
+    assert_eq!(Image::decode(&img.encode().unwrap()).unwrap(), img);
+
+    let raw = img.to_raw();
+    assert_eq!(raw.len(), 800 * 600 * 4);
+    assert_eq!(Image::from_raw(*img.header(), &raw).unwrap(), img);
+    assert!(Image::from_raw(*img.header(), &raw[1..]).is_err());
+
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("image.qoi");
+    img.save(&path).unwrap();
+    let (header, pixels) = qoi::read(&path).unwrap();
+    assert_eq!(Image::try_from((header, pixels)).unwrap(), img);
+}
+
+#[test]
+fn test_image_view() {
+    let img = Image::open("./imgs/dice.qoi").unwrap();
+    let view = img.view();
+    let sub = view.sub_view(100, 50, 300, 200).unwrap();
+    assert_eq!((sub.width(), sub.height(), sub.stride()), (300, 200, 800));
+    assert_eq!(sub.get_pixel(0, 0), img.get_pixel(100, 50));
+    assert_eq!(sub.get_pixel(299, 199), img.get_pixel(399, 249));
+    assert_eq!(sub.get_pixel(300, 0), None);
+    assert_eq!(sub.rows().count(), 200);
+    assert_eq!(sub.rows().next().unwrap().len(), 300);
+
+    let nested = sub.sub_view(10, 20, 5, 5).unwrap();
+    assert_eq!(nested.get_pixel(1, 1), img.get_pixel(111, 71));
+    assert!(view.sub_view(700, 0, 101, 1).is_none());
+    assert!(view.sub_view(0, 0, 0, 1).is_none());
+    assert!(view.sub_view(u32::MAX, 0, 2, 1).is_none());
+
+    // Encoding the view without copying matches encoding a copy
+    let copy = sub.to_image(img.channels(), img.colorspace());
+    assert_eq!(
+        sub.encode(img.channels(), img.colorspace()).unwrap(),
+        copy.encode().unwrap()
+    );
+    let (header, pixels) = qoi::decode::decode_allocated(&copy.encode().unwrap()).unwrap();
+    assert_eq!((header.width, header.height), (300, 200));
+    assert_eq!(pixels[..], *copy.pixels());
+    assert_eq!(
+        view.encode(img.channels(), img.colorspace()).unwrap(),
+        img.encode().unwrap()
+    );
+}
+
+#[test]
+fn test_image_view_errors() {
+    let pixels = vec![Pixel::def(); 10];
+    // The last row doesn't need to be padded to the stride
+    assert!(ImageView::with_stride(&pixels, 2, 3, 4).is_ok());
+    assert!(matches!(
+        ImageView::with_stride(&pixels, 3, 3, 2),
+        Err(ImageError::InvalidStride { .. })
+    ));
+    assert!(matches!(
+        ImageView::with_stride(&pixels, 3, 3, 4),
+        Err(ImageError::SizeMismatch {
+            expected_size: 11,
+            received_size: 10
+        })
+    ));
+    assert!(matches!(
+        ImageView::new(&pixels, 0, 1),
+        Err(ImageError::InvalidDimensions(_))
+    ));
+}
+
+#[test]
+fn test_image_view_mut() {
+    let header = Header::new(4, 4, ColorChannel::RGBA, ColorSpace::SRGB);
+    let mut img = Image::filled(header, Pixel::zero()).unwrap();
+    let red = Pixel::from([255, 0, 0]);
+    let blue = Pixel::from([0, 0, 255]);
+
+    let mut view = img.view_mut();
+    view.sub_view_mut(1, 1, 2, 2).unwrap().fill(red);
+    assert_eq!(view.set_pixel(3, 3, blue), Some(Pixel::zero()));
+
+    // Copy the top left 2x2 pixels to the bottom left
+    let src = Image::new(
+        Header::new(2, 2, ColorChannel::RGBA, ColorSpace::SRGB),
+        view.sub_view(0, 0, 2, 2)
+            .unwrap()
+            .rows()
+            .flatten()
+            .copied()
+            .collect(),
+    )
+    .unwrap();
+    let mut dst = view.sub_view_mut(0, 2, 2, 2).unwrap();
+    dst.copy_from(&src.view()).unwrap();
+    assert!(matches!(
+        dst.copy_from(&src.view().sub_view(0, 0, 1, 2).unwrap()),
+        Err(ImageError::DimensionMismatch { .. })
+    ));
+
+    let (z, r, b) = (Pixel::zero(), red, blue);
+    #[rustfmt::skip]
+    let expected = [
+        z, z, z, z,
+        z, r, r, z,
+        z, z, r, z,
+        z, r, z, b,
+    ];
+    assert_eq!(img.pixels(), expected);
+}
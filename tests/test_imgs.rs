@@ -1,35 +1,34 @@
-use qoi;
 use std::{
     fs::{self, File},
     io::Read,
     path::Path,
 };
 
+fn testcard_header() -> qoi::Header {
+    qoi::Header {
+        width: 256,
+        height: 256,
+        channels: qoi::ColorChannel::RGBA,
+        colorspace: qoi::ColorSpace::SRGB,
+    }
+}
+
 #[test]
 fn test_decoder() {
-    let decoded_path = Path::new("./imgs/testcard.bin");
     let encoded_path = Path::new("./imgs/testcard.qoi");
 
-    let mut decoded_file = File::open(decoded_path).unwrap();
-    let mut encoded_file = File::open(encoded_path).unwrap();
-
-    let mut decoded_buf = Vec::<u8>::new();
-    decoded_file.read_to_end(&mut decoded_buf).unwrap();
-
-    println!("Decoded Buffer Length: {}", decoded_buf.len());
+    let decoded_header = testcard_header();
+    let decoded_pixels = qoi::raw::read(
+        "./imgs/testcard.bin",
+        &decoded_header,
+        qoi::raw::RawLayout::RGBA,
+    )
+    .unwrap();
 
+    let mut encoded_file = File::open(encoded_path).unwrap();
     let mut encoded_buf = Vec::<u8>::new();
     encoded_file.read_to_end(&mut encoded_buf).unwrap();
 
-    let decoded_pixels: Vec<qoi::Pixel> = decoded_buf.chunks(4).map(|chunk| chunk.into()).collect();
-
-    let decoded_header = qoi::Header {
-        width: 256,
-        height: 256,
-        channels: qoi::ColorChannel::RGBA,
-        colorspace: qoi::ColorSpace::SRGB,
-    };
-
     let (header, pixels) = qoi::decode::decode_allocated(&encoded_buf).unwrap();
 
     assert_eq!(header, decoded_header);
@@ -39,26 +38,14 @@ fn test_decoder() {
 
 #[test]
 fn test_encoder() {
-    let decoded_path = Path::new("./imgs/testcard.bin");
     let encoded_path = Path::new("./imgs/testcard.qoi");
 
-    let mut decoded_file = File::open(decoded_path).unwrap();
     let mut encoded_file = File::open(encoded_path).unwrap();
-
-    let mut decoded_buf = Vec::<u8>::new();
-    decoded_file.read_to_end(&mut decoded_buf).unwrap();
-
     let mut encoded_buf = Vec::<u8>::new();
     encoded_file.read_to_end(&mut encoded_buf).unwrap();
 
-    let header = qoi::Header {
-        width: 256,
-        height: 256,
-        channels: qoi::ColorChannel::RGBA,
-        colorspace: qoi::ColorSpace::SRGB,
-    };
-
-    let pixels: Vec<qoi::Pixel> = decoded_buf.chunks(4).map(|chunk| chunk.into()).collect();
+    let header = testcard_header();
+    let pixels = qoi::raw::read("./imgs/testcard.bin", &header, qoi::raw::RawLayout::RGBA).unwrap();
 
     let res = qoi::encode::encode_allocated(&header, &pixels).unwrap();
 
@@ -66,13 +53,25 @@ fn test_encoder() {
     assert_eq!(res, encoded_buf);
 }
 
+#[test]
+fn test_raw_fixture() {
+    // testcard.bin can be reproduced from testcard.qoi
+    let (header, pixels) = qoi::read("./imgs/testcard.qoi").unwrap();
+    let res = qoi::raw::encode(&header, &pixels, qoi::raw::RawLayout::RGBA).unwrap();
+    assert_eq!(res, fs::read("./imgs/testcard.bin").unwrap());
+}
+
 #[test]
 fn test_imgs() {
     fs::read_dir(Path::new("./imgs"))
         .unwrap()
         .filter(|x| {
             x.is_ok()
-                && format!("{}", x.as_ref().unwrap().file_name().to_str().unwrap())
+                && x.as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_str()
+                    .unwrap()
                     .ends_with(".qoi")
         })
         .for_each(|file| {
@@ -85,9 +84,9 @@ fn test_imgs() {
 
             let res = qoi::read(path.clone());
             assert!(res.is_ok(), "{:?}", res.unwrap_err());
-            let (header, mut pixels) = res.unwrap();
+            let (header, pixels) = res.unwrap();
 
-            let res = qoi::encode::encode_allocated(&header, &mut pixels);
+            let res = qoi::encode::encode_allocated(&header, &pixels);
             assert!(res.is_ok(), "{:?}", res.unwrap_err());
             let vec = res.unwrap();
 
@@ -95,3 +94,22 @@ fn test_imgs() {
             assert_eq!(vec, buf);
         })
 }
+
+#[test]
+fn test_validate() {
+    let buf = fs::read("./imgs/testcard.qoi").unwrap();
+    assert!(qoi::decode::validate(&buf).is_ok());
+
+    let mut trailing = buf.clone();
+    trailing.push(0);
+    assert!(matches!(
+        qoi::decode::validate(&trailing),
+        Err(qoi::decode::DecodeError::TrailingData(1))
+    ));
+
+    let missing_end = &buf[..buf.len() - 1];
+    assert!(matches!(
+        qoi::decode::validate(missing_end),
+        Err(qoi::decode::DecodeError::MissingEndMarker)
+    ));
+}
@@ -0,0 +1,238 @@
+use qoi::{png::*, zlib, ColorChannel, Pixel};
+use std::{fs, path::Path};
+
+fn png_imgs() -> Vec<(std::path::PathBuf, std::path::PathBuf)> {
+    fs::read_dir(Path::new("./imgs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+        .map(|path| (path.clone(), path.with_extension("qoi")))
+        .collect()
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let mut content = kind.to_vec();
+    content.extend_from_slice(data);
+    out.extend_from_slice(&content);
+    out.extend_from_slice(&crc32(&content).to_be_bytes());
+}
+
+// Builds a PNG from unfiltered scanlines, a filter type byte of 0 is added to each line
+fn build_png(
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+    chunks: &[(&[u8; 4], &[u8])],
+    lines: &[Vec<u8>],
+) -> Vec<u8> {
+    let mut out = PNG_SIGNATURE.to_vec();
+    let mut ihdr = width.to_be_bytes().to_vec();
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[bit_depth, color_type, 0, 0, interlaced as u8]);
+    chunk(&mut out, b"IHDR", &ihdr);
+    for (kind, data) in chunks {
+        chunk(&mut out, kind, data);
+    }
+    let raw: Vec<u8> = lines
+        .iter()
+        .flat_map(|line| std::iter::once(0).chain(line.iter().copied()))
+        .collect();
+    chunk(&mut out, b"IDAT", &zlib::compress(&raw));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[test]
+fn test_decode_matches_qoi() {
+    for (png_path, qoi_path) in png_imgs() {
+        let (png_header, png_pixels) = read(&png_path).unwrap();
+        let (qoi_header, qoi_pixels) = qoi::read(&qoi_path).unwrap();
+        assert_eq!(png_header.width, qoi_header.width);
+        assert_eq!(png_header.height, qoi_header.height);
+        assert_eq!(png_pixels, qoi_pixels, "{:?}", png_path);
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    for (_, qoi_path) in png_imgs() {
+        let (header, pixels) = qoi::read(&qoi_path).unwrap();
+        let encoded = encode(&header, &pixels).unwrap();
+        let (res_header, res_pixels) = decode(&encoded).unwrap();
+        assert_eq!(res_header.channels, header.channels);
+        assert_eq!(res_pixels, pixels);
+    }
+}
+
+#[test]
+fn test_zlib_roundtrip() {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut noise = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as u8
+    };
+    let inputs: Vec<Vec<u8>> = vec![
+        vec![],
+        vec![42],
+        b"abcabcabcabcabcabcabcabc".to_vec(),
+        vec![7; 100_000],
+        (0..100_000).map(|_| noise()).collect(),
+        (0..100_000)
+            .map(|i| (i % 251) as u8 ^ (noise() & 3))
+            .collect(),
+    ];
+    for input in inputs {
+        let compressed = zlib::compress(&input);
+        assert_eq!(zlib::decompress(&compressed, input.len()).unwrap(), input);
+        if !input.is_empty() {
+            assert!(matches!(
+                zlib::decompress(&compressed, input.len() - 1),
+                Err(zlib::ZlibError::OutputTooLarge(_))
+            ));
+        }
+    }
+}
+
+#[test]
+fn test_gray_low_bit_depth() {
+    // 1 bit gray, 10 pixels wide: 0b1010101010
+    let lines = vec![
+        vec![0b1010_1010, 0b1000_0000],
+        vec![0b0000_0000, 0b0100_0000],
+    ];
+    let (header, pixels) = decode(&build_png(10, 2, 1, 0, false, &[], &lines)).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    let white = Pixel::from([255, 255, 255, 255]);
+    let black = Pixel::from([0, 0, 0, 255]);
+    for x in 0..10 {
+        assert_eq!(pixels[x], if x % 2 == 0 { white } else { black });
+        assert_eq!(pixels[10 + x], if x == 9 { white } else { black });
+    }
+}
+
+#[test]
+fn test_palette_with_transparency() {
+    let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+    let trns = [128, 0];
+    let lines = vec![vec![0x01, 0x20]];
+    let png = build_png(
+        3,
+        1,
+        4,
+        3,
+        false,
+        &[(b"PLTE", &palette), (b"tRNS", &trns)],
+        &lines,
+    );
+    let (header, pixels) = decode(&png).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGBA);
+    assert_eq!(
+        pixels,
+        vec![
+            Pixel::from([255, 0, 0, 128]),
+            Pixel::from([0, 255, 0, 0]),
+            Pixel::from([0, 0, 255, 255]),
+        ]
+    );
+
+    let lines = vec![vec![0x30]];
+    let png = build_png(1, 1, 4, 3, false, &[(b"PLTE", &palette)], &lines);
+    assert!(matches!(
+        decode(&png),
+        Err(PngError::InvalidPaletteIndex(3))
+    ));
+}
+
+#[test]
+fn test_gray16_with_transparency() {
+    let lines = vec![vec![0x12, 0x34, 0xff, 0xff, 0x00, 0x80]];
+    let png = build_png(3, 1, 16, 0, false, &[(b"tRNS", &[0x12, 0x34])], &lines);
+    let (header, pixels) = decode(&png).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGBA);
+    assert_eq!(
+        pixels,
+        vec![
+            Pixel::from([0x12, 0x12, 0x12, 0]),
+            Pixel::from([255, 255, 255, 255]),
+            Pixel::from([0, 0, 0, 255]),
+        ]
+    );
+}
+
+#[test]
+fn test_adam7() {
+    let (width, height) = (13usize, 11usize);
+    let expected: Vec<Pixel> = (0..width * height)
+        .map(|i| Pixel::from([i as u8, (i * 7) as u8, (i * 13) as u8]))
+        .collect();
+
+    let passes = [
+        (0, 0, 8, 8),
+        (4, 0, 8, 8),
+        (0, 4, 4, 8),
+        (2, 0, 4, 4),
+        (0, 2, 2, 4),
+        (1, 0, 2, 2),
+        (0, 1, 1, 2),
+    ];
+    let mut lines = Vec::new();
+    for (x0, y0, dx, dy) in passes {
+        if x0 >= width {
+            continue;
+        }
+        for y in (y0..height).step_by(dy) {
+            let line = (x0..width)
+                .step_by(dx)
+                .flat_map(|x| {
+                    let px = expected[y * width + x];
+                    [px.r, px.g, px.b]
+                })
+                .collect();
+            lines.push(line);
+        }
+    }
+
+    let png = build_png(width as u32, height as u32, 8, 2, true, &[], &lines);
+    let (header, pixels) = decode(&png).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    assert_eq!(pixels, expected);
+}
+
+#[test]
+fn test_invalid() {
+    let lines = vec![vec![1, 2, 3]];
+    let png = build_png(1, 1, 8, 2, false, &[], &lines);
+    assert!(decode(&png).is_ok());
+
+    let mut corrupt = png.clone();
+    corrupt[20] ^= 1;
+    assert!(matches!(decode(&corrupt), Err(PngError::CrcMismatch(_))));
+
+    assert!(matches!(
+        decode(&png[..png.len() - 4]),
+        Err(PngError::UnexpectedEnd)
+    ));
+    assert!(matches!(decode(&png[1..]), Err(PngError::InvalidSignature)));
+
+    let png = build_png(1, 1, 8, 2, false, &[(b"ABCD", &[])], &lines);
+    assert!(matches!(decode(&png), Err(PngError::InvalidChunk(_))));
+
+    let png = build_png(0, 1, 8, 2, false, &[], &lines);
+    assert!(matches!(decode(&png), Err(PngError::InvalidDimensions(_))));
+
+    let png = build_png(2, 2, 8, 2, false, &[], &lines);
+    assert!(matches!(decode(&png), Err(PngError::MissingPixels { .. })));
+
+    // Decompression stops at the raw size of the image, instead of inflating all of it
+    let lines = vec![vec![0; 3]; 1_000_000];
+    let png = build_png(1, 1, 8, 2, false, &[], &lines);
+    assert!(matches!(
+        decode(&png),
+        Err(PngError::ZlibError(zlib::ZlibError::OutputTooLarge(4)))
+    ));
+}
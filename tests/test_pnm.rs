@@ -0,0 +1,103 @@
+mod common;
+
+use qoi::{pnm::*, ColorChannel, Pixel};
+
+#[test]
+fn test_roundtrip() {
+    for (header, pixels) in common::qoi_imgs() {
+        let encoded = encode(&header, &pixels, PnmFormat::Pam).unwrap();
+        let (res_header, res_pixels) = decode(&encoded).unwrap();
+        assert_eq!(res_header, header);
+        assert_eq!(res_pixels, pixels);
+
+        let encoded = encode(&header, &pixels, PnmFormat::Ppm).unwrap();
+        let (res_header, res_pixels) = decode(&encoded).unwrap();
+        assert_eq!(res_header.channels, ColorChannel::RGB);
+        for (res, px) in res_pixels.iter().zip(&pixels) {
+            assert_eq!([res.r, res.g, res.b, res.a], [px.r, px.g, px.b, 255]);
+        }
+    }
+}
+
+#[test]
+fn test_pgm() {
+    let header = qoi::Header::new(2, 1, ColorChannel::RGB, qoi::ColorSpace::SRGB);
+    let pixels = [Pixel::from([255, 0, 0]), Pixel::from([10, 10, 10])];
+    let encoded = encode(&header, &pixels, PnmFormat::Pgm).unwrap();
+    assert_eq!(encoded, b"P5\n2 1\n255\n\x4c\x0a");
+
+    let (header, pixels) = decode(&encoded).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    assert_eq!(pixels[0], Pixel::from([76, 76, 76]));
+}
+
+#[test]
+fn test_16_bit_with_comments() {
+    let mut data = b"P6 # comment\n3 # another\n1\n65535\n".to_vec();
+    data.extend_from_slice(&[0xff, 0xff, 0x00, 0x00, 0x80, 0x00]);
+    data.extend_from_slice(&[0x00, 0x80, 0x7f, 0x7f, 0x01, 0x01]);
+    data.extend_from_slice(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc]);
+    let (header, pixels) = decode(&data).unwrap();
+    assert_eq!((header.width, header.height), (3, 1));
+    assert_eq!(
+        pixels,
+        vec![
+            Pixel::from([255, 0, 128]),
+            Pixel::from([0, 127, 1]),
+            Pixel::from([0x12, 0x56, 0x9a]),
+        ]
+    );
+}
+
+#[test]
+fn test_pam_tuple_types() {
+    let data = b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 2\nMAXVAL 15\n# comment\nTUPLTYPE GRAYSCALE_ALPHA\nENDHDR\n\x0f\x00\x05\x0f";
+    let (header, pixels) = decode(data).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGBA);
+    assert_eq!(
+        pixels,
+        vec![
+            Pixel::from([255, 255, 255, 0]),
+            Pixel::from([85, 85, 85, 255])
+        ]
+    );
+
+    let data = b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 3\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n\0\0\0";
+    assert!(matches!(
+        decode(data),
+        Err(PnmError::UnsupportedTupleType(_))
+    ));
+    let data = b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 3\nMAXVAL 255\nTUPLTYPE CMYK\nENDHDR\n\0\0\0";
+    assert!(matches!(
+        decode(data),
+        Err(PnmError::UnsupportedTupleType(_))
+    ));
+}
+
+#[test]
+fn test_invalid() {
+    assert!(matches!(
+        decode(b"P3\n1 1\n255\n0 0 0"),
+        Err(PnmError::InvalidMagic)
+    ));
+    assert!(matches!(
+        decode(b"P6\n1 1\n0\n\0\0\0"),
+        Err(PnmError::InvalidHeader)
+    ));
+    assert!(matches!(decode(b"P6\n1 1"), Err(PnmError::InvalidHeader)));
+    assert!(matches!(
+        decode(b"P6\n0 1\n255\n"),
+        Err(PnmError::InvalidDimensions(_))
+    ));
+    assert!(matches!(
+        decode(b"P6\n2 1\n255\n\0\0\0"),
+        Err(PnmError::MissingPixels { .. })
+    ));
+    assert!(matches!(
+        decode(b"P5\n1 1\n7\n\x08"),
+        Err(PnmError::SampleOutOfRange {
+            sample: 8,
+            maxval: 7
+        })
+    ));
+}
@@ -0,0 +1,65 @@
+use qoi::{raw::*, ColorChannel, ColorSpace, Header, Pixel};
+
+fn pixels() -> Vec<Pixel> {
+    vec![Pixel::from([1, 2, 3, 4]), Pixel::from([5, 6, 7, 8])]
+}
+
+#[test]
+fn test_layouts() {
+    let rgba = Header::new(2, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let rgb = Header::new(2, 1, ColorChannel::RGB, ColorSpace::SRGB);
+    let cases: [(RawLayout, [u8; 8], [u8; 6]); 4] = [
+        (
+            RawLayout::RGBA,
+            [1, 2, 3, 4, 5, 6, 7, 8],
+            [1, 2, 3, 5, 6, 7],
+        ),
+        (
+            RawLayout::BGRA,
+            [3, 2, 1, 4, 7, 6, 5, 8],
+            [3, 2, 1, 7, 6, 5],
+        ),
+        (
+            RawLayout::ARGB,
+            [4, 1, 2, 3, 8, 5, 6, 7],
+            [1, 2, 3, 5, 6, 7],
+        ),
+        (
+            RawLayout::ABGR,
+            [4, 3, 2, 1, 8, 7, 6, 5],
+            [3, 2, 1, 7, 6, 5],
+        ),
+    ];
+    for (layout, four, three) in cases {
+        assert_eq!(encode(&rgba, &pixels(), layout).unwrap(), four);
+        assert_eq!(decode(&four, &rgba, layout).unwrap(), pixels());
+
+        assert_eq!(encode(&rgb, &pixels(), layout).unwrap(), three);
+        let opaque: Vec<Pixel> = pixels()
+            .into_iter()
+            .map(|px| Pixel { a: 255, ..px })
+            .collect();
+        assert_eq!(decode(&three, &rgb, layout).unwrap(), opaque);
+    }
+}
+
+#[test]
+fn test_invalid() {
+    let header = Header::new(2, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    assert!(matches!(
+        decode(&[0; 7], &header, RawLayout::RGBA),
+        Err(RawError::SizeMismatch {
+            expected_size: 8,
+            received_size: 7
+        })
+    ));
+    assert!(matches!(
+        encode(&header, &pixels()[..1], RawLayout::RGBA),
+        Err(RawError::MissingPixels { .. })
+    ));
+    let header = Header::new(0, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    assert!(matches!(
+        decode(&[], &header, RawLayout::RGBA),
+        Err(RawError::InvalidDimensions(_))
+    ));
+}
@@ -0,0 +1,249 @@
+// Differential tests against the reference implementation in ref_qoi/qoi.h.
+// The reference is compiled with the C compiler found on the build machine (`$CC` or `cc`).
+// If no C compiler is available, the tests fail instead of silently passing.
+
+use qoi::{decode, encode, ColorChannel, ColorSpace, Header, Pixel};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+fn reference() -> &'static Path {
+    static REFERENCE: OnceLock<PathBuf> = OnceLock::new();
+    REFERENCE.get_or_init(|| {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("qoi_ref");
+        let cc = env::var("CC").unwrap_or("cc".to_string());
+        let res = Command::new(&cc)
+            .arg("-std=c99")
+            .arg("-O2")
+            .arg("-I")
+            .arg(manifest.join("ref_qoi"))
+            .arg(manifest.join("tests/ref/qoi_ref.c"))
+            .arg("-o")
+            .arg(&out)
+            .status();
+        match res {
+            Ok(status) if status.success() => out,
+            res => panic!(
+                "Couldn't compile the reference implementation with `{}` ({:?}). \
+                 Set `CC` to a working C compiler to run the differential tests.",
+                cc, res
+            ),
+        }
+    })
+}
+
+fn tmp_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+fn ref_encode(reference: &Path, header: &Header, pixels: &[Pixel], name: &str) -> Vec<u8> {
+    let raw: Vec<u8> = pixels
+        .iter()
+        .flat_map(|px| [px.r, px.g, px.b, px.a])
+        .enumerate()
+        .filter(|(i, _)| header.channels == ColorChannel::RGBA || i % 4 != 3)
+        .map(|(_, byte)| byte)
+        .collect();
+    let (raw_path, out_path) = (
+        tmp_path(&format!("{}.raw", name)),
+        tmp_path(&format!("{}.qoi", name)),
+    );
+    fs::write(&raw_path, raw).unwrap();
+    let status = Command::new(reference)
+        .arg("encode")
+        .arg(header.width.to_string())
+        .arg(header.height.to_string())
+        .arg(u8::from(header.channels).to_string())
+        .arg(u8::from(header.colorspace).to_string())
+        .arg(&raw_path)
+        .arg(&out_path)
+        .status()
+        .unwrap();
+    assert!(status.success(), "Reference encoder failed for {}", name);
+    fs::read(out_path).unwrap()
+}
+
+fn ref_decode(reference: &Path, encoded: &[u8], name: &str) -> Vec<Pixel> {
+    let (in_path, raw_path) = (
+        tmp_path(&format!("{}.ours.qoi", name)),
+        tmp_path(&format!("{}.ours.raw", name)),
+    );
+    fs::write(&in_path, encoded).unwrap();
+    let status = Command::new(reference)
+        .arg("decode")
+        .arg(&in_path)
+        .arg(&raw_path)
+        .status()
+        .unwrap();
+    assert!(status.success(), "Reference decoder failed for {}", name);
+    fs::read(raw_path)
+        .unwrap()
+        .chunks(4)
+        .map(|chunk| chunk.into())
+        .collect()
+}
+
+// Xorshift, so the generated images are the same on every run
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.next() as u8
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+fn noise(rng: &mut Rng, amount: usize) -> Vec<Pixel> {
+    (0..amount)
+        .map(|_| [rng.byte(), rng.byte(), rng.byte(), rng.byte()].into())
+        .collect()
+}
+
+fn gradient(rng: &mut Rng, width: u32, height: u32) -> Vec<Pixel> {
+    let (sx, sy) = (rng.below(5) as u32 + 1, rng.below(5) as u32 + 1);
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| Pixel {
+            r: (x * sx) as u8,
+            g: (y * sy) as u8,
+            b: (x + y) as u8,
+            a: 255 - (y * sy / 2) as u8,
+        })
+        .collect()
+}
+
+fn runs(rng: &mut Rng, amount: usize) -> Vec<Pixel> {
+    let palette = noise(rng, 8);
+    let mut pixels = Vec::with_capacity(amount);
+    while pixels.len() < amount {
+        let px = palette[rng.below(8) as usize];
+        let len = rng.below(150) as usize + 1;
+        pixels.extend(std::iter::repeat_n(px, len.min(amount - pixels.len())));
+    }
+    pixels
+}
+
+fn alpha_edges(rng: &mut Rng, width: u32, height: u32) -> Vec<Pixel> {
+    let edge = rng.below(width as u64) as u32;
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let base = Pixel::from([(x * 3) as u8, (y * 2) as u8, 128]);
+            match (x + y) % 7 {
+                _ if x < edge => base,
+                0 => Pixel::zero(),
+                1 => Pixel { a: 0, ..base },
+                2 => Pixel { a: 128, ..base },
+                _ => base,
+            }
+        })
+        .collect()
+}
+
+fn test_images() -> Vec<(String, Header, Vec<Pixel>)> {
+    let mut rng = Rng(0x9E3779B97F4A7C15);
+    let mut images = Vec::new();
+    for (width, height) in [(1, 1), (7, 3), (64, 64), (300, 200)] {
+        for channels in [ColorChannel::RGB, ColorChannel::RGBA] {
+            let amount = (width * height) as usize;
+            let generated = [
+                ("noise", noise(&mut rng, amount)),
+                ("gradient", gradient(&mut rng, width, height)),
+                ("runs", runs(&mut rng, amount)),
+                ("alpha_edges", alpha_edges(&mut rng, width, height)),
+            ];
+            for (kind, mut pixels) in generated {
+                if channels == ColorChannel::RGB {
+                    pixels.iter_mut().for_each(|px| px.a = 255);
+                }
+                // The reference encoder starts with a run if the first pixel is the default pixel,
+                // which this crate deliberately doesn't do. That case is covered by `test_first_pixel_run`.
+                if pixels[0] == Pixel::def() {
+                    pixels[0].r = 1;
+                }
+                let name = format!("{}_{}x{}_{}", kind, width, height, u8::from(channels));
+                let header = Header::new(width, height, channels, ColorSpace::SRGB);
+                images.push((name, header, pixels));
+            }
+        }
+    }
+    images
+}
+
+#[test]
+fn test_reference_encode() {
+    let reference = reference();
+    for (name, header, pixels) in test_images() {
+        let expected = ref_encode(reference, &header, &pixels, &name);
+        let mut buffer = vec![0; header.max_size()];
+        let size = encode::encode(&header, &pixels, &mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..size],
+            &expected[..],
+            "Encodings differ for {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_reference_decode() {
+    let reference = reference();
+    for (name, header, pixels) in test_images() {
+        // Tests run in parallel, so they mustn't share temporary files
+        let name = format!("decode_{}", name);
+        let encoded = ref_encode(reference, &header, &pixels, &name);
+        let mut decoded = vec![Pixel::def(); header.pixel_amount()];
+        let decoded_header = decode::decode(&encoded, &mut decoded).unwrap();
+        assert_eq!(decoded_header, header, "Headers differ for {}", name);
+        assert_eq!(decoded, pixels, "Decoded pixels differ for {}", name);
+
+        let ours = encode::encode_allocated(&header, &pixels).unwrap();
+        assert_eq!(
+            ref_decode(reference, &ours, &name),
+            pixels,
+            "Reference decoded pixels differ for {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_first_pixel_run() {
+    // See https://github.com/phoboslab/qoi/issues/258
+    let reference = reference();
+    let header = Header::new(5, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let pixels = vec![
+        Pixel::def(),
+        Pixel::def(),
+        Pixel::from_hex("f00"),
+        Pixel::def(),
+        Pixel::def(),
+    ];
+
+    let expected = ref_encode(reference, &header, &pixels, "first_pixel_run");
+    let ours = encode::encode_allocated(&header, &pixels).unwrap();
+    assert_ne!(ours, expected);
+
+    // A leading run repeats the default pixel
+    for encoded in [&ours, &expected] {
+        let mut decoded = vec![Pixel::zero(); header.pixel_amount()];
+        decode::decode(encoded, &mut decoded).unwrap();
+        assert_eq!(decoded, pixels);
+    }
+    assert_eq!(ref_decode(reference, &ours, "first_pixel_run"), pixels);
+}
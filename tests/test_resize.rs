@@ -0,0 +1,79 @@
+use qoi::{image::Image, resize::*, ColorChannel, ColorSpace, DimensionsError, Header, Pixel};
+
+const FILTERS: [Filter; 4] = [
+    Filter::Nearest,
+    Filter::Bilinear,
+    Filter::Box,
+    Filter::Lanczos3,
+];
+
+#[test]
+fn test_resize_identity() {
+    // Opaque, since fully transparent pixels lose their color
+    let (header, mut pixels) = Image::open("./imgs/testcard_rgba.qoi")
+        .unwrap()
+        .into_parts();
+    pixels.iter_mut().for_each(|px| px.a = 255);
+    for filter in FILTERS {
+        let res = resize(&header, &pixels, 256, 256, filter).unwrap();
+        assert!(res == (header, pixels.clone()), "{:?}", filter);
+    }
+}
+
+#[test]
+fn test_resize_uniform() {
+    let header = Header::new(37, 23, ColorChannel::RGBA, ColorSpace::SRGB);
+    let px = Pixel::from([200, 100, 30, 180]);
+    let pixels = vec![px; header.pixel_amount()];
+    for filter in FILTERS {
+        for (width, height) in [(10, 7), (100, 50), (1, 1)] {
+            let (res_header, res) = resize(&header, &pixels, width, height, filter).unwrap();
+            assert_eq!((res_header.width, res_header.height), (width, height));
+            assert!(res.iter().all(|&p| p == px), "{:?}", filter);
+        }
+    }
+}
+
+#[test]
+fn test_resize_linear_light() {
+    // Black & white columns average to half the light, which is 188 in sRGB
+    let black = Pixel::from([0, 0, 0]);
+    let white = Pixel::from([255, 255, 255]);
+    let mut header = Header::new(2, 1, ColorChannel::RGB, ColorSpace::SRGB);
+    let (_, res) = resize(&header, &[black, white], 1, 1, Filter::Box).unwrap();
+    assert_eq!(res, [Pixel::from([188, 188, 188])]);
+
+    header.colorspace = ColorSpace::LINEAR;
+    let (_, res) = resize(&header, &[black, white], 1, 1, Filter::Box).unwrap();
+    assert_eq!(res, [Pixel::from([128, 128, 128])]);
+}
+
+#[test]
+fn test_resize_premultiplied() {
+    // The color of the transparent pixel doesn't bleed into the result
+    let header = Header::new(2, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let pixels = [Pixel::from([255, 0, 0, 255]), Pixel::from([0, 255, 0, 0])];
+    for filter in [Filter::Bilinear, Filter::Box] {
+        let (_, res) = resize(&header, &pixels, 1, 1, filter).unwrap();
+        assert_eq!(res, [Pixel::from([255, 0, 0, 128])], "{:?}", filter);
+    }
+    let (_, res) = resize(&header, &pixels, 4, 1, Filter::Nearest).unwrap();
+    assert_eq!(res, [pixels[0], pixels[0], pixels[1], pixels[1]]);
+}
+
+#[test]
+fn test_resize_errors() {
+    let header = Header::new(2, 2, ColorChannel::RGBA, ColorSpace::SRGB);
+    let pixels = vec![Pixel::def(); 4];
+    assert!(matches!(
+        resize(&header, &pixels, 0, 5, Filter::Box),
+        Err(ResizeError::InvalidDimensions(DimensionsError {
+            width: 0,
+            height: 5
+        }))
+    ));
+    assert!(matches!(
+        resize(&header, &pixels[..3], 1, 1, Filter::Box),
+        Err(ResizeError::MissingPixels { .. })
+    ));
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use qoi::scan::*;
+
+#[test]
+fn test_parallel_decode() {
+    for buf in common::qoi_files() {
+        let expected = qoi::decode::decode_allocated(&buf).unwrap();
+        assert_eq!(decode_parallel(&buf).unwrap(), expected);
+
+        for interval in [1000, 4096, 77777] {
+            let index = ScanIndex::scan(&buf, interval).unwrap();
+            for threads in [1, 3, 16] {
+                assert_eq!(index.decode(&buf, threads).unwrap(), expected.1);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_index_serialization() {
+    let buf = common::qoi_files().remove(0);
+    let index = ScanIndex::scan(&buf, 5000).unwrap();
+    let bytes = index.to_bytes();
+    let read = ScanIndex::from_bytes(&bytes).unwrap();
+    assert_eq!(read, index);
+    assert_eq!(
+        read.decode(&buf, 4).unwrap(),
+        qoi::decode::decode_allocated(&buf).unwrap().1
+    );
+
+    assert!(ScanIndex::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_index_mismatch() {
+    let files = common::qoi_files();
+    let index = ScanIndex::scan(&files[0], 5000).unwrap();
+    assert!(index.decode(&files[1], 4).is_err());
+}
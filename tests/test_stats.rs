@@ -0,0 +1,69 @@
+use qoi::{
+    stats::*, ColorChannel, ColorSpace, Header, Opcode, Pixel, HEADER_SIZE, STREAM_END_SIZE,
+};
+use std::fs;
+
+#[test]
+fn test_stats_totals() {
+    for path in [
+        "./imgs/dice.qoi",
+        "./imgs/testcard.qoi",
+        "./imgs/baboon.qoi",
+    ] {
+        let buffer = fs::read(path).unwrap();
+        let stats = analyze(&buffer).unwrap();
+
+        let bytes: usize = stats.opcodes.iter().map(|op| op.bytes).sum();
+        assert_eq!(HEADER_SIZE + bytes + STREAM_END_SIZE, buffer.len());
+        let pixels: usize = stats.opcodes.iter().map(|op| op.pixels).sum();
+        assert_eq!(pixels, stats.header.pixel_amount());
+
+        let run = stats.opcode(Opcode::RUN);
+        let runs: usize = stats.run_lengths.iter().sum();
+        let run_pixels: usize = (1..=RUN_MAX)
+            .zip(stats.run_lengths)
+            .map(|(len, n)| len * n)
+            .sum();
+        assert_eq!(runs, run.chunks);
+        assert_eq!(run_pixels, run.pixels);
+        assert_eq!(stats.pixel_chunks + run.chunks, stats.chunks());
+
+        let share: f64 = Opcode::ALL.iter().map(|&op| stats.byte_share(op)).sum();
+        assert!((share - 1.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_stats_opcodes() {
+    // RGB, RUN 3, DIFF, LUMA, RGBA, INDEX (the first pixel)
+    let header = Header::new(8, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let mut pixels = vec![Pixel::from([100, 50, 20, 255]); 4];
+    pixels.push(Pixel::from([101, 49, 20, 255]));
+    pixels.push(Pixel::from([111, 59, 25, 255]));
+    pixels.push(Pixel::from([111, 59, 25, 128]));
+    pixels.push(Pixel::from([100, 50, 20, 255]));
+    let buffer = qoi::encode::encode_allocated(&header, &pixels).unwrap();
+    let stats = analyze(&buffer).unwrap();
+
+    for opcode in Opcode::ALL {
+        assert_eq!(stats.opcode(opcode).chunks, 1, "{}", opcode.name());
+        assert_eq!(stats.opcode(opcode).bytes, opcode.size());
+    }
+    assert_eq!(stats.run_lengths[2], 1);
+    assert_eq!(stats.pixel_chunks, 5);
+    assert_eq!(stats.index_hit_rate(), 0.2);
+    assert_eq!(stats.collisions, 0);
+}
+
+#[test]
+fn test_stats_collision() {
+    // Both pixels hash to the same index slot, so the third pixel misses the index
+    let a = Pixel::from([1, 0, 0, 255]);
+    let b = Pixel::from([65, 0, 0, 255]);
+    assert_eq!(a.pixel_hash(), b.pixel_hash());
+    let header = Header::new(3, 1, ColorChannel::RGBA, ColorSpace::SRGB);
+    let buffer = qoi::encode::encode_allocated(&header, &[a, b, a]).unwrap();
+    let stats = analyze(&buffer).unwrap();
+    assert_eq!(stats.opcode(Opcode::INDEX).chunks, 0);
+    assert_eq!(stats.collisions, 1);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use qoi::stripe::*;
+use std::fs;
+
+fn qoi_imgs() -> Vec<(qoi::Header, Vec<qoi::Pixel>, Vec<u8>)> {
+    common::qoi_paths()
+        .into_iter()
+        .map(|path| {
+            let (header, pixels) = qoi::read(&path).unwrap();
+            (header, pixels, fs::read(&path).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn test_single_stripe_is_standard() {
+    for (header, pixels, encoded) in qoi_imgs() {
+        let res = encode_striped(&header, &pixels, 1).unwrap();
+        assert_eq!(res, encoded);
+    }
+}
+
+#[test]
+fn test_striped_roundtrip() {
+    for (header, pixels, _) in qoi_imgs() {
+        for stripes in [2, 3, 7, 64, header.height as usize + 5] {
+            let res = encode_striped(&header, &pixels, stripes).unwrap();
+
+            let (striped_header, striped_pixels) = decode_striped(&res).unwrap();
+            assert_eq!(striped_header, header);
+            assert_eq!(striped_pixels, pixels);
+
+            // Striped images are still valid for sequential decoders
+            let (seq_header, seq_pixels) = qoi::decode::decode_allocated(&res).unwrap();
+            assert_eq!(seq_header, header);
+            assert_eq!(seq_pixels, pixels);
+        }
+    }
+}
+
+#[test]
+fn test_stripe_zero_pixel() {
+    // Transparent black hashes to the index slot 0, which is zero-initialized in a fresh decoder
+    let header = qoi::Header::new(4, 4, qoi::ColorChannel::RGBA, qoi::ColorSpace::SRGB);
+    let mut pixels = vec![qoi::Pixel::from_hex("f00f"); 16];
+    pixels[0] = qoi::Pixel::zero();
+    pixels[9] = qoi::Pixel::zero();
+    pixels[14] = qoi::Pixel::zero();
+
+    let res = encode_striped(&header, &pixels, 4).unwrap();
+    assert_eq!(decode_striped(&res).unwrap().1, pixels);
+    assert_eq!(qoi::decode::decode_allocated(&res).unwrap().1, pixels);
+}
+
+#[test]
+fn test_invalid_stripe_table() {
+    let (header, pixels, _) = qoi_imgs().remove(0);
+    let mut res = encode_striped(&header, &pixels, 4).unwrap();
+    let len = res.len();
+    // Claim more stripes than there are offsets
+    res[len - 5] = 200;
+    assert!(decode_striped(&res).is_err());
+}
+
+#[test]
+fn test_many_stripes() {
+    // One stripe per row, far more than there are threads
+    let header = qoi::Header::new(3, 20000, qoi::ColorChannel::RGB, qoi::ColorSpace::SRGB);
+    let pixels: Vec<_> = (0..header.pixel_amount())
+        .map(|i| qoi::Pixel::from([i as u8, (i >> 8) as u8, 7]))
+        .collect();
+    let res = encode_striped(&header, &pixels, 20000).unwrap();
+    assert_eq!(decode_striped(&res).unwrap().1, pixels);
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use qoi::{tga::*, ColorChannel, ColorSpace, Header, Pixel};
+
+fn build_tga(
+    image_type: u8,
+    color_map: Option<(u16, u8, &[u8])>,
+    width: u16,
+    height: u16,
+    depth: u8,
+    descriptor: u8,
+    data: &[u8],
+) -> Vec<u8> {
+    let (first, map_depth, map) = color_map.unwrap_or((0, 0, &[]));
+    let map_length = match map_depth {
+        0 => 0,
+        depth => map.len() / (depth as usize / 8),
+    };
+    // Includes an image ID, that needs to be skipped
+    let mut out = vec![3, color_map.is_some() as u8, image_type];
+    out.extend_from_slice(&first.to_le_bytes());
+    out.extend_from_slice(&(map_length as u16).to_le_bytes());
+    out.extend_from_slice(&[map_depth, 0, 0, 0, 0]);
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    out.extend_from_slice(&[depth, descriptor]);
+    out.extend_from_slice(b"ID!");
+    out.extend_from_slice(map);
+    out.extend_from_slice(data);
+    out
+}
+
+fn pixels(colors: &[[u8; 3]]) -> Vec<Pixel> {
+    colors.iter().map(|&color| Pixel::from(color)).collect()
+}
+
+const RED: [u8; 3] = [255, 0, 0];
+const GREEN: [u8; 3] = [0, 255, 0];
+const BLUE: [u8; 3] = [0, 0, 255];
+const WHITE: [u8; 3] = [255, 255, 255];
+
+#[test]
+fn test_roundtrip() {
+    for (header, pixels) in common::qoi_imgs() {
+        for rle in [false, true] {
+            let encoded = encode(&header, &pixels, rle).unwrap();
+            let (res_header, res_pixels) = decode(&encoded).unwrap();
+            assert_eq!(res_header, header);
+            assert_eq!(res_pixels, pixels);
+        }
+    }
+}
+
+#[test]
+fn test_orientation() {
+    let data = [0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255];
+    let (header, res) = decode(&build_tga(2, None, 2, 2, 24, 0x20, &data)).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    assert_eq!(res, pixels(&[RED, GREEN, BLUE, WHITE]));
+
+    // Bottom-up is the default
+    let (_, res) = decode(&build_tga(2, None, 2, 2, 24, 0, &data)).unwrap();
+    assert_eq!(res, pixels(&[BLUE, WHITE, RED, GREEN]));
+
+    let (_, res) = decode(&build_tga(2, None, 2, 2, 24, 0x30, &data)).unwrap();
+    assert_eq!(res, pixels(&[GREEN, RED, WHITE, BLUE]));
+}
+
+#[test]
+fn test_rle() {
+    // A run packet of 4 crosses the first scanline, followed by a raw packet of 2
+    let data = [0x83, 0, 0, 255, 128, 0x01, 0, 255, 0, 0, 255, 0, 0, 64];
+    let (header, res) = decode(&build_tga(10, None, 3, 2, 32, 0x28, &data)).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGBA);
+    let red = Pixel::from([255, 0, 0, 128]);
+    assert_eq!(
+        res,
+        vec![
+            red,
+            red,
+            red,
+            red,
+            Pixel::from([0, 255, 0, 0]),
+            Pixel::from([0, 0, 255, 64]),
+        ]
+    );
+
+    // Grayscale
+    let data = [0x81, 10, 0x00, 20];
+    let (_, res) = decode(&build_tga(11, None, 3, 1, 8, 0x20, &data)).unwrap();
+    assert_eq!(res, pixels(&[[10; 3], [10; 3], [20; 3]]));
+}
+
+#[test]
+fn test_color_map() {
+    // The color map starts at index 1
+    let map = [0, 0, 255, 0, 255, 0];
+    let data = [1, 2, 2, 1];
+    let tga = build_tga(1, Some((1, 24, &map)), 2, 2, 8, 0x20, &data);
+    let (header, res) = decode(&tga).unwrap();
+    assert_eq!(header.channels, ColorChannel::RGB);
+    assert_eq!(res, pixels(&[RED, GREEN, GREEN, RED]));
+
+    let tga = build_tga(1, Some((1, 24, &map)), 1, 1, 8, 0x20, &[0]);
+    assert!(matches!(
+        decode(&tga),
+        Err(TgaError::InvalidColorMapIndex(0))
+    ));
+    let tga = build_tga(1, Some((1, 24, &map)), 1, 1, 8, 0x20, &[3]);
+    assert!(matches!(
+        decode(&tga),
+        Err(TgaError::InvalidColorMapIndex(3))
+    ));
+}
+
+#[test]
+fn test_16_bit() {
+    let data = [0x00, 0x7c, 0xe0, 0x03, 0x1f, 0x00];
+    let (_, res) = decode(&build_tga(2, None, 3, 1, 16, 0x20, &data)).unwrap();
+    assert_eq!(res, pixels(&[RED, GREEN, BLUE]));
+}
+
+#[test]
+fn test_invalid() {
+    assert!(matches!(decode(&[0; 17]), Err(TgaError::MissingHeader)));
+    assert!(matches!(
+        decode(&build_tga(2, None, 1, 1, 8, 0, &[0])),
+        Err(TgaError::UnsupportedFormat {
+            image_type: 2,
+            pixel_depth: 8
+        })
+    ));
+    assert!(matches!(
+        decode(&build_tga(2, None, 0, 1, 24, 0, &[])),
+        Err(TgaError::InvalidDimensions(_))
+    ));
+    let header = Header::new(70000, 1, ColorChannel::RGB, ColorSpace::SRGB);
+    assert!(matches!(
+        encode(&header, &vec![Pixel::def(); 70000], false),
+        Err(TgaError::TooLarge {
+            width: 70000,
+            height: 1
+        })
+    ));
+    assert!(matches!(
+        decode(&build_tga(2, None, 2, 1, 24, 0, &[0; 5])),
+        Err(TgaError::MissingPixels {
+            expected_size: 2,
+            received_size: 1
+        })
+    ));
+    assert!(matches!(
+        decode(&build_tga(10, None, 2, 1, 24, 0, &[0x80, 0, 0, 0])),
+        Err(TgaError::MissingPixels {
+            expected_size: 2,
+            received_size: 1
+        })
+    ));
+}
@@ -0,0 +1,100 @@
+use qoi::{image::Image, transform::*, ColorChannel, ColorSpace, Header, Pixel};
+
+const ALL: [Transform; 6] = [
+    Transform::FlipHorizontal,
+    Transform::FlipVertical,
+    Transform::Rotate90,
+    Transform::Rotate180,
+    Transform::Rotate270,
+    Transform::Transpose,
+];
+
+// 3x2 image, each pixel's red value is its index
+fn small() -> (Header, Vec<Pixel>) {
+    let header = Header::new(3, 2, ColorChannel::RGB, ColorSpace::SRGB);
+    (header, (0..6).map(|i| Pixel::from([i, 0, 0])).collect())
+}
+
+fn reds(pixels: &[Pixel]) -> Vec<u8> {
+    pixels.iter().map(|px| px.r).collect()
+}
+
+#[test]
+fn test_transform_small() {
+    // 0 1 2
+    // 3 4 5
+    let expected: [(Transform, (u32, u32), [u8; 6]); 6] = [
+        (Transform::FlipHorizontal, (3, 2), [2, 1, 0, 5, 4, 3]),
+        (Transform::FlipVertical, (3, 2), [3, 4, 5, 0, 1, 2]),
+        (Transform::Rotate90, (2, 3), [3, 0, 4, 1, 5, 2]),
+        (Transform::Rotate180, (3, 2), [5, 4, 3, 2, 1, 0]),
+        (Transform::Rotate270, (2, 3), [2, 5, 1, 4, 0, 3]),
+        (Transform::Transpose, (2, 3), [0, 3, 1, 4, 2, 5]),
+    ];
+    for (transform, dimensions, res) in expected {
+        let (mut header, mut pixels) = small();
+        let (copy_header, copy) = transform.applied(&header, &pixels).unwrap();
+        transform.apply(&mut header, &mut pixels).unwrap();
+        assert_eq!((header.width, header.height), dimensions, "{:?}", transform);
+        assert_eq!(reds(&pixels), res, "{:?}", transform);
+        assert_eq!((copy_header, copy), (header, pixels), "{:?}", transform);
+    }
+}
+
+#[test]
+fn test_transform_in_place_matches_copy() {
+    let img = Image::open("./imgs/testcard_rgba.qoi").unwrap();
+    // Crop to a non-square size, which needs a non-trivial in-place transposition
+    let img = img.view().sub_view(3, 10, 200, 77).unwrap();
+    let img = img.to_image(ColorChannel::RGBA, ColorSpace::SRGB);
+    for transform in ALL {
+        let (mut header, mut pixels) = img.clone().into_parts();
+        let copy = transform.applied(&header, &pixels).unwrap();
+        transform.apply(&mut header, &mut pixels).unwrap();
+        assert!(copy == (header, pixels), "{:?}", transform);
+    }
+
+    let mut rotated = img.clone();
+    for _ in 0..4 {
+        rotated.transform(Transform::Rotate90);
+    }
+    assert!(rotated == img);
+    // Rotating by 90 degrees is transposing & flipping horizontally
+    rotated.transform(Transform::Rotate270);
+    rotated.transform(Transform::Transpose);
+    rotated.transform(Transform::FlipHorizontal);
+    assert!(rotated == img);
+}
+
+#[test]
+fn test_transform_missing_pixels() {
+    let (mut header, mut pixels) = small();
+    pixels.pop();
+    assert!(matches!(
+        Transform::Rotate90.apply(&mut header, &mut pixels),
+        Err(TransformError::MissingPixels {
+            expected_size: 6,
+            received_size: 5
+        })
+    ));
+    assert_eq!((header.width, header.height), (3, 2));
+}
+
+#[test]
+fn test_transform_empty() {
+    for (width, height) in [(0, 0), (0, 5), (5, 0)] {
+        let header = Header::new(width, height, ColorChannel::RGB, ColorSpace::SRGB);
+        for transform in ALL {
+            let mut res_header = header;
+            let mut pixels = vec![Pixel::def(); 3];
+            transform.apply(&mut res_header, &mut pixels).unwrap();
+            let dims = transform.dimensions(width, height);
+            assert_eq!((res_header.width, res_header.height), dims);
+            assert_eq!(pixels, [Pixel::def(); 3]);
+
+            let (res_header, res) = transform.applied(&header, &[]).unwrap();
+            assert_eq!((res_header.width, res_header.height), dims);
+            assert!(res.is_empty());
+        }
+    }
+}